@@ -0,0 +1,36 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nalgebra::Vector3;
+use thrdrender::{stress_mesh, Camera, Color, Screen, Transform};
+
+/// A large screen with a dense sphere drawn on it, as `build` sees it at the
+/// end of a frame.
+fn drawn_screen(colored: bool) -> Screen {
+    let mut screen = Screen::new(400, 200);
+    let (vertices, edges) = stress_mesh(2000);
+    let transform = Transform::from_rotation(Vector3::new(0.3, 0.6, 0.0));
+    let camera = Camera::new(Vector3::new(0.0, 0.0, -3.0), 1.0);
+    if colored {
+        let colors = vec![Color::new(255, 128, 0); vertices.len()];
+        screen.draw_mesh_colored(&vertices, &edges, &colors, &transform, &camera);
+    } else {
+        screen.draw_mesh(&vertices, &edges, &transform, &camera);
+    }
+    screen
+}
+
+fn build(c: &mut Criterion) {
+    for (name, colored) in [("build 400x200", false), ("build 400x200 colored", true)] {
+        let mut screen = drawn_screen(colored);
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                screen.build();
+                black_box(screen.buffer().len())
+            })
+        });
+    }
+}
+
+criterion_group!(benches, build);
+criterion_main!(benches);
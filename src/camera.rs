@@ -0,0 +1,437 @@
+use nalgebra::{Matrix3, Rotation3, Unit, Vector3};
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::{rng::SplitMix64, Screen};
+
+/// A camera path maps the animation time to a camera position.
+pub type CameraPath = Box<dyn Fn(f64) -> Vector3<f64>>;
+
+/// Which way +z points relative to +x and +y in world space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Handedness {
+    /// +z points away from a viewer with +x right and +y up; the renderer's
+    /// native convention.
+    #[default]
+    Left,
+    /// +z points towards that viewer, as in OpenGL and most modelling tools.
+    /// World space is mirrored in z on the way into camera space, so without
+    /// a target the camera looks down -z.
+    Right,
+}
+
+pub struct Camera {
+    pub position: Vector3<f64>,
+    /// When set the camera turns to face this point; otherwise it looks down
+    /// the +z axis.
+    pub target: Option<Vector3<f64>>,
+    pub display_surface_z: f64,
+    /// Overrides `position` on every `update` when set. Cameras made by
+    /// `with_path` also keep `target` on the origin.
+    pub path: Option<CameraPath>,
+    pub handedness: Handedness,
+    /// Radians the image is turned counter-clockwise about the view axis.
+    pub roll: f64,
+    shake: Option<Shake>,
+    /// The shake offset currently added to `position`.
+    shake_offset: Vector3<f64>,
+}
+
+/// A shake started by `Camera::trigger_shake`.
+struct Shake {
+    intensity: f64,
+    duration: f64,
+    /// Time of the first `update` after the trigger.
+    start: Option<f64>,
+    rng: SplitMix64,
+}
+
+/// Seed for the shake directions, so that shakes are reproducible.
+const SHAKE_SEED: u64 = 0x5eed;
+
+impl Camera {
+    pub fn new(position: Vector3<f64>, display_surface_z: f64) -> Self {
+        Self {
+            position,
+            target: None,
+            display_surface_z,
+            path: None,
+            handedness: Handedness::Left,
+            roll: 0.0,
+            shake: None,
+            shake_offset: Vector3::zeros(),
+        }
+    }
+
+    /// A camera that follows `path`, starting where it is at time 0, and
+    /// always looks at the origin.
+    pub fn with_path(path: CameraPath, display_surface_z: f64) -> Self {
+        let position = path(0.0);
+        Self {
+            target: Some(Vector3::zeros()),
+            path: Some(path),
+            ..Self::new(position, display_surface_z)
+        }
+    }
+
+    /// Builds a path tracing a Lissajous figure around `center`, with each axis
+    /// following `amplitude * sin(frequency * t + phase)`.
+    pub fn lissajous(
+        center: Vector3<f64>,
+        amplitude: Vector3<f64>,
+        frequency: Vector3<f64>,
+        phase: Vector3<f64>,
+    ) -> CameraPath {
+        Box::new(move |t| center + amplitude.component_mul(&(frequency * t + phase).map(f64::sin)))
+    }
+
+    /// Moves the camera along its path, if it has one, and applies any shake.
+    pub fn update(&mut self, time: f64) {
+        match &self.path {
+            Some(path) => self.position = path(time),
+            None => self.position -= self.shake_offset,
+        }
+        self.shake_offset = self.next_shake_offset(time);
+        self.position += self.shake_offset;
+    }
+
+    /// Shakes the camera for `duration` units of `update` time, starting at
+    /// the next `update`. Each update moves it `intensity` away from where it
+    /// would be in a random direction, decaying linearly to zero. A new shake
+    /// replaces one in progress.
+    pub fn trigger_shake(&mut self, intensity: f64, duration: f64) {
+        self.shake = Some(Shake {
+            intensity,
+            duration,
+            start: None,
+            rng: SplitMix64::new(SHAKE_SEED),
+        });
+    }
+
+    /// How far the current shake has moved the camera from `position`'s
+    /// unshaken value.
+    pub fn shake_offset(&self) -> Vector3<f64> {
+        self.shake_offset
+    }
+
+    fn next_shake_offset(&mut self, time: f64) -> Vector3<f64> {
+        let Some(shake) = &mut self.shake else {
+            return Vector3::zeros();
+        };
+        let start = *shake.start.get_or_insert(time);
+        let remaining = 1.0 - (time - start) / shake.duration;
+        if remaining.is_nan() || remaining <= 0.0 {
+            self.shake = None;
+            return Vector3::zeros();
+        }
+        let direction = Vector3::from_fn(|_, _| shake.rng.next_f64() * 2.0 - 1.0)
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(Vector3::x);
+        direction * shake.intensity * remaining
+    }
+
+    /// Rotation taking world-space directions into camera space, where the
+    /// camera looks down +z with +y up, turned by `roll`. For right-handed
+    /// worlds it also mirrors z, so it is then not a pure rotation.
+    pub fn view_rotation(&self) -> Matrix3<f64> {
+        let mirror = match self.handedness {
+            Handedness::Left => Matrix3::identity(),
+            Handedness::Right => Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, -1.0)),
+        };
+        let (sin, cos) = self.roll.sin_cos();
+        let roll = Matrix3::new(
+            cos, -sin, 0.0,
+            sin, cos, 0.0,
+            0.0, 0.0, 1.0,
+        );
+        let Some(target) = self.target else {
+            return roll * mirror;
+        };
+        let forward = (mirror * (target - self.position))
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        // Looking straight up or down leaves world-up parallel to the view
+        // direction, so fall back to +z as the up hint.
+        let right = Vector3::y()
+            .cross(&forward)
+            .try_normalize(f64::EPSILON)
+            .unwrap_or_else(|| Vector3::z().cross(&forward).normalize());
+        let up = forward.cross(&right);
+        roll * Matrix3::from_rows(&[right.transpose(), up.transpose(), forward.transpose()]) * mirror
+    }
+
+    /// Transforms a world-space point into camera space.
+    pub fn to_view(&self, point: Vector3<f64>) -> Vector3<f64> {
+        self.view_rotation() * (point - self.position)
+    }
+
+    /// Like `Screen::project_3d_point`, but honoring the camera's orientation.
+    pub fn project(&self, screen: &Screen, point: Vector3<f64>) -> Option<(usize, usize)> {
+        let rotation = self.view_rotation();
+        let reference_z = (rotation * -self.position).z;
+        screen.project_view_point(rotation * (point - self.position), reference_z, self.display_surface_z)
+    }
+}
+
+impl Default for Camera {
+    /// The demo's camera: slightly above the origin, looking down +z.
+    fn default() -> Self {
+        Self::new(Vector3::new(0.0, 2.0, -5.0), 1.0)
+    }
+}
+
+/// Whether distant points shrink towards the center of the screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProjectionMode {
+    #[default]
+    Perspective,
+    Orthographic,
+}
+
+impl ProjectionMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            ProjectionMode::Perspective => ProjectionMode::Orthographic,
+            ProjectionMode::Orthographic => ProjectionMode::Perspective,
+        }
+    }
+
+    /// Sets `screen.perspective_strength` to match this mode.
+    pub fn apply(self, screen: &mut Screen) {
+        screen.perspective_strength = match self {
+            ProjectionMode::Perspective => 1.0,
+            ProjectionMode::Orthographic => 0.0,
+        };
+    }
+}
+
+/// Circles the camera around `target`, always facing it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrbitCamera {
+    /// The axis the orbit turns about, by the right-hand rule for positive
+    /// `speed`. A zero axis orbits about +y.
+    pub axis: Vector3<f64>,
+    pub radius: f64,
+    /// Radians per unit of time.
+    pub speed: f64,
+    pub target: Vector3<f64>,
+}
+
+impl OrbitCamera {
+    pub fn new(axis: Vector3<f64>, radius: f64, speed: f64, target: Vector3<f64>) -> Self {
+        Self { axis, radius, speed, target }
+    }
+
+    /// The camera position at `time`. At time 0 it is on the side of the
+    /// target towards -z, or towards +y for orbits about the z axis.
+    pub fn position(&self, time: f64) -> Vector3<f64> {
+        let axis = Unit::try_new(self.axis, f64::EPSILON).unwrap_or_else(Vector3::y_axis);
+        let start = [-Vector3::z(), Vector3::y()]
+            .into_iter()
+            .find_map(|direction| (direction - axis.scale(direction.dot(&axis))).try_normalize(1e-9))
+            .unwrap_or_else(Vector3::x);
+        self.target + Rotation3::from_axis_angle(&axis, self.speed * time) * start * self.radius
+    }
+
+    /// Moves `camera` to its place on the orbit at `time` and points it at
+    /// the target.
+    pub fn apply(&self, camera: &mut Camera, time: f64) {
+        camera.position = self.position(time);
+        camera.target = Some(self.target);
+        camera.path = None;
+    }
+}
+
+/// Preset CAD-style camera orientations, all looking at the origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandardView {
+    /// Above the origin looking down, with +z towards the top of the screen.
+    Top,
+    /// In front of the origin on -z, looking down +z.
+    Front,
+    /// On +x, looking back towards -x.
+    Right,
+    /// Along the (1, 1, -1) diagonal, so all three axes are foreshortened
+    /// equally.
+    Isometric,
+}
+
+impl StandardView {
+    /// Points `camera` at the origin from `distance` away along this view's
+    /// direction and switches `screen` to orthographic projection. Isometric
+    /// is orthographic by definition as well.
+    pub fn apply(self, screen: &mut Screen, camera: &mut Camera, distance: f64) {
+        let direction = match self {
+            StandardView::Top => Vector3::new(0.0, 1.0, 0.0),
+            StandardView::Front => Vector3::new(0.0, 0.0, -1.0),
+            StandardView::Right => Vector3::new(1.0, 0.0, 0.0),
+            StandardView::Isometric => Vector3::new(1.0, 1.0, -1.0).normalize(),
+        };
+        camera.position = direction * distance;
+        camera.target = Some(Vector3::zeros());
+        camera.path = None;
+        screen.perspective_strength = 0.0;
+    }
+}
+
+impl FromStr for StandardView {
+    type Err = ParseViewError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "top" => Ok(StandardView::Top),
+            "front" => Ok(StandardView::Front),
+            "right" => Ok(StandardView::Right),
+            "isometric" | "iso" => Ok(StandardView::Isometric),
+            _ => Err(ParseViewError(name.to_string())),
+        }
+    }
+}
+
+/// Returned when a name does not match any `StandardView`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseViewError(pub String);
+
+impl fmt::Display for ParseViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown view {:?}, expected top, front, right or isometric", self.0)
+    }
+}
+
+impl Error for ParseViewError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rotate_point, BackfaceStyle, Transform, CUBE_FACES, CUBE_VERTICES};
+
+    #[test]
+    fn lissajous_path_moves_and_stays_finite() {
+        let path = Camera::lissajous(
+            Vector3::new(0.0, 0.0, -5.0),
+            Vector3::new(2.0, 1.0, 0.5),
+            Vector3::new(0.5, 1.5, 0.25),
+            Vector3::zeros(),
+        );
+        let (start, later) = (path(0.0), path(std::f64::consts::PI));
+        assert_eq!(start, Vector3::new(0.0, 0.0, -5.0));
+        assert!(later.iter().all(|coordinate| coordinate.is_finite()));
+        assert!((later - start).norm() > 0.1);
+
+        let mut camera = Camera::with_path(path, 1.0);
+        assert_eq!(camera.position, start);
+        camera.update(std::f64::consts::PI);
+        assert_eq!(camera.position, later);
+        // Wherever the path has taken it, the camera faces the origin.
+        let origin = camera.to_view(Vector3::zeros());
+        assert!(origin.x.abs() < 1e-12 && origin.y.abs() < 1e-12 && origin.z > 0.0, "{:?}", origin);
+    }
+
+    #[test]
+    fn top_view_looks_down_on_the_floor() {
+        let (mut screen, mut camera) = (Screen::new(80, 40), Camera::default());
+        StandardView::Top.apply(&mut screen, &mut camera, 10.0);
+        assert_eq!(camera.position, Vector3::new(0.0, 10.0, 0.0));
+        assert_eq!(screen.perspective_strength, 0.0);
+        assert!((camera.to_view(Vector3::zeros()) - Vector3::new(0.0, 0.0, 10.0)).norm() < 1e-12);
+
+        // +x is to the right and +z towards the top of the screen.
+        assert_eq!(camera.project(&screen, Vector3::zeros()), Some((40, 20)));
+        assert_eq!(camera.project(&screen, Vector3::new(2.0, 0.0, 0.0)), Some((48, 20)));
+        assert_eq!(camera.project(&screen, Vector3::new(0.0, 0.0, 4.0)), Some((40, 12)));
+    }
+
+    #[test]
+    fn standard_views_parse_from_names() {
+        assert_eq!("Top".parse(), Ok(StandardView::Top));
+        assert_eq!("iso".parse(), Ok(StandardView::Isometric));
+        assert_eq!("back".parse::<StandardView>(), Err(ParseViewError("back".to_string())));
+    }
+
+    #[test]
+    fn mirrored_points_project_the_same_under_the_other_handedness() {
+        let screen = Screen::new(80, 40);
+        let left = Camera::new(Vector3::new(0.0, 0.5, -5.0), 1.0);
+        let mut right = Camera::new(Vector3::new(0.0, 0.5, 5.0), 1.0);
+        right.handedness = Handedness::Right;
+        for vertex in CUBE_VERTICES {
+            let mirrored = Vector3::new(vertex.x, vertex.y, -vertex.z);
+            assert_eq!(right.to_view(mirrored), left.to_view(vertex));
+            assert_eq!(right.project(&screen, mirrored), left.project(&screen, vertex));
+        }
+    }
+
+    #[test]
+    fn quarter_roll_turns_up_into_left() {
+        let screen = Screen::new(80, 40);
+        let mut camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let above = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(camera.project(&screen, above), Some((40, 16)));
+        // Turning the image counter-clockwise swings the top to the left.
+        camera.roll = std::f64::consts::FRAC_PI_2;
+        assert_eq!(camera.project(&screen, above), Some((32, 20)));
+    }
+
+    #[test]
+    fn shake_decays_to_nothing_and_restores_the_position() {
+        let mut camera = Camera::default();
+        let home = camera.position;
+        camera.trigger_shake(0.5, 1.0);
+        let mut magnitudes = Vec::new();
+        for step in 0..4 {
+            camera.update(10.0 + step as f64 * 0.25);
+            let offset = camera.shake_offset();
+            assert!((camera.position - (home + offset)).norm() < 1e-12);
+            magnitudes.push(offset.norm());
+        }
+        assert!((magnitudes[0] - 0.5).abs() < 1e-12);
+        assert!(magnitudes.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", magnitudes);
+
+        camera.update(11.0);
+        assert_eq!(camera.shake_offset(), Vector3::zeros());
+        assert!((camera.position - home).norm() < 1e-12);
+    }
+
+    #[test]
+    fn orbit_turns_a_quarter_about_its_axis_at_a_fixed_radius() {
+        let target = Vector3::new(1.0, 0.0, 0.0);
+        let orbit = OrbitCamera::new(Vector3::y(), 4.0, 2.0, target);
+        let start = orbit.position(0.0) - target;
+        let quarter = orbit.position(std::f64::consts::FRAC_PI_4) - target;
+        assert!((start - Vector3::new(0.0, 0.0, -4.0)).norm() < 1e-12);
+        assert!((quarter - Vector3::new(-4.0, 0.0, 0.0)).norm() < 1e-12, "{:?}", quarter);
+        assert!((quarter.norm() - 4.0).abs() < 1e-12 && quarter.dot(&start).abs() < 1e-12);
+
+        let mut camera = Camera::default();
+        orbit.apply(&mut camera, std::f64::consts::FRAC_PI_4);
+        assert_eq!(camera.position, orbit.position(std::f64::consts::FRAC_PI_4));
+        assert!((camera.to_view(target) - Vector3::new(0.0, 0.0, 4.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn mirrored_mesh_renders_the_same_under_the_other_handedness() {
+        let render = |vertices: &[Vector3<f64>], faces: &[[usize; 4]], camera: &Camera| {
+            let mut screen = Screen::new(60, 30);
+            screen.enable_object_ids();
+            screen.backface_style = BackfaceStyle::Cull;
+            screen.fill_faces(vertices, faces, &Transform::default(), camera).unwrap();
+            screen.face_pixel_counts()
+        };
+        let vertices = CUBE_VERTICES.map(|vertex| rotate_point(vertex, Vector3::new(0.4, 0.6, 0.0)));
+        let left = render(&vertices, &CUBE_FACES, &Camera::new(Vector3::new(0.0, 0.5, -5.0), 1.0));
+
+        // Mirroring in z turns the faces clockwise, so their corners are
+        // listed in reverse to keep them counter-clockwise from outside.
+        let mirrored = vertices.map(|vertex| Vector3::new(vertex.x, vertex.y, -vertex.z));
+        let reversed = CUBE_FACES.map(|[a, b, c, d]| [d, c, b, a]);
+        let mut camera = Camera::new(Vector3::new(0.0, 0.5, 5.0), 1.0);
+        camera.handedness = Handedness::Right;
+        let right = render(&mirrored, &reversed, &camera);
+
+        assert_eq!(left.len(), 3);
+        assert_eq!(left, right);
+        // Drawn the native way, the mirrored mesh shows its back faces.
+        camera.handedness = Handedness::Left;
+        camera.position.z = -5.0;
+        assert_ne!(render(&mirrored, &reversed, &camera).keys().collect::<Vec<_>>(), left.keys().collect::<Vec<_>>());
+    }
+}
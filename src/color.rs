@@ -0,0 +1,101 @@
+use std::{env, fmt::Write};
+
+pub(crate) const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Linear interpolation between `self` (t = 0) and `other` (t = 1).
+    pub fn lerp(self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+        )
+    }
+
+    /// The nearest entry of the 6x6x6 color cube in the 256-color palette.
+    pub fn to_ansi256(self) -> u8 {
+        let level = |channel: u8| ((channel as u16 * 5 + 127) / 255) as u8;
+        16 + 36 * level(self.r) + 6 * level(self.g) + level(self.b)
+    }
+
+    /// Appends the foreground escape sequence for this color at `support`;
+    /// nothing for monochrome.
+    pub(crate) fn push_escape(self, buffer: &mut String, support: ColorSupport) {
+        match support {
+            ColorSupport::TrueColor => write!(buffer, "\x1b[38;2;{};{};{}m", self.r, self.g, self.b).unwrap(),
+            ColorSupport::Ansi256 => write!(buffer, "\x1b[38;5;{}m", self.to_ansi256()).unwrap(),
+            ColorSupport::Monochrome => {}
+        }
+    }
+}
+
+/// How many colors a terminal can show, from fewest to most.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// No color escapes are written at all.
+    Monochrome,
+    Ansi256,
+    #[default]
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Reads the `COLORTERM` and `TERM` environment variables.
+    pub fn detect() -> Self {
+        Self::from_env(env::var("COLORTERM").ok().as_deref(), env::var("TERM").ok().as_deref())
+    }
+
+    /// Maps values of `COLORTERM` and `TERM` to a support level. Terminals
+    /// limited to the basic 16 colors count as monochrome.
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::TrueColor;
+        }
+        match term {
+            Some(term) if term.ends_with("-direct") => ColorSupport::TrueColor,
+            Some(term) if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Monochrome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_maps_to_color_support() {
+        assert_eq!(ColorSupport::from_env(Some("truecolor"), None), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(Some("24bit"), Some("xterm")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(None, Some("xterm-direct")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(None, Some("xterm-256color")), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env(None, Some("xterm")), ColorSupport::Monochrome);
+        assert_eq!(ColorSupport::from_env(None, None), ColorSupport::Monochrome);
+    }
+
+    #[test]
+    fn escapes_follow_the_support_level() {
+        let color = Color::new(255, 128, 0);
+        let escape = |support| {
+            let mut buffer = String::new();
+            color.push_escape(&mut buffer, support);
+            buffer
+        };
+        assert_eq!(escape(ColorSupport::TrueColor), "\x1b[38;2;255;128;0m");
+        assert_eq!(escape(ColorSupport::Ansi256), "\x1b[38;5;214m");
+        assert_eq!(escape(ColorSupport::Monochrome), "");
+    }
+}
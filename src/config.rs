@@ -0,0 +1,213 @@
+use nalgebra::Vector3;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "terminal")]
+use crate::ClearStrategy;
+use crate::{Camera, Screen, SPIN_THRESHOLD};
+
+/// Live-tweakable settings, read from a TOML file such as:
+///
+/// ```toml
+/// rotation_velocity = [0.01, 0.0, 0.01]
+/// pixel_char = "#"
+/// max_duration = 30.0
+/// clear_strategy = "alt-screen"
+///
+/// [camera]
+/// position = [0.0, 2.0, -5.0]
+/// display_surface_z = 1.0
+/// ```
+///
+/// Missing keys keep their defaults, which match the demo.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Radians added to the rotation about each axis per frame.
+    pub rotation_velocity: [f64; 3],
+    pub camera: CameraConfig,
+    pub pixel_char: char,
+    pub background_char: char,
+    /// Seconds after which the demo exits; it runs forever if unset.
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub max_duration: Option<f64>,
+    /// Busy-waits the end of each frame's pause for steadier timing, at the
+    /// cost of CPU time.
+    pub spin_sleep: bool,
+    /// One of `full-clear`, `cursor-home`, `alt-screen` or `none`.
+    #[cfg(feature = "terminal")]
+    pub clear_strategy: ClearStrategy,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraConfig {
+    pub position: [f64; 3],
+    pub display_surface_z: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rotation_velocity: [0.01, 0.0, 0.01],
+            camera: CameraConfig::default(),
+            pixel_char: '.',
+            background_char: ' ',
+            max_duration: None,
+            spin_sleep: true,
+            #[cfg(feature = "terminal")]
+            clear_strategy: ClearStrategy::FullClear,
+        }
+    }
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 2.0, -5.0],
+            display_surface_z: 1.0,
+        }
+    }
+}
+
+impl Config {
+    pub fn rotation_velocity(&self) -> Vector3<f64> {
+        Vector3::from(self.rotation_velocity)
+    }
+
+    /// `max_duration` as a `Duration`; `None` if unset or not a valid
+    /// duration.
+    pub fn max_duration(&self) -> Option<Duration> {
+        self.max_duration.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+    }
+
+    /// How long `sleep_spinning` should busy-wait at the end of a pause.
+    pub fn spin(&self) -> Duration {
+        if self.spin_sleep {
+            SPIN_THRESHOLD
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Copies the camera and character settings onto `camera` and `screen`.
+    pub fn apply(&self, screen: &mut Screen, camera: &mut Camera) {
+        camera.position = Vector3::from(self.camera.position);
+        camera.display_surface_z = self.camera.display_surface_z;
+        screen.pixel_char = self.pixel_char;
+        screen.background_char = self.background_char;
+    }
+}
+
+/// Accepts only finite, non-negative seconds, so that a bad value is
+/// rejected as invalid config rather than turning into a `Duration` panic.
+fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let seconds = Option::<f64>::deserialize(deserializer)?;
+    match seconds {
+        Some(value) if !value.is_finite() || value < 0.0 => Err(D::Error::custom(format!(
+            "expected a finite, non-negative number of seconds, found {}",
+            value
+        ))),
+        _ => Ok(seconds),
+    }
+}
+
+pub fn parse_config(source: &str) -> Result<Config, toml::de::Error> {
+    toml::from_str(source)
+}
+
+/// Polls a config file's modification time and reloads it when it changes.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    config: Config,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            config: Config::default(),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Re-reads the file if it changed since the last poll, returning true
+    /// when a new config was loaded. Unreadable or invalid files are reported
+    /// on stderr and leave the previous config in place.
+    pub fn poll(&mut self) -> bool {
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(error) => {
+                if self.modified.is_some() {
+                    eprintln!("failed to read {}: {}", self.path.display(), error);
+                    self.modified = None;
+                }
+                return false;
+            }
+        };
+        if self.modified == Some(modified) {
+            return false;
+        }
+        self.modified = Some(modified);
+
+        let result = fs::read_to_string(&self.path)
+            .map_err(|error| error.to_string())
+            .and_then(|source| parse_config(&source).map_err(|error| error.to_string()));
+        match result {
+            Ok(config) => {
+                self.config = config;
+                true
+            }
+            Err(error) => {
+                eprintln!("invalid config {}: {}", self.path.display(), error);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_config() {
+        let config = parse_config(
+            "rotation_velocity = [0.02, 0.0, 0.03]\npixel_char = \"#\"\n\n[camera]\ndisplay_surface_z = 2.0\n",
+        )
+        .unwrap();
+        assert_eq!(config.rotation_velocity(), Vector3::new(0.02, 0.0, 0.03));
+        assert_eq!(config.pixel_char, '#');
+        assert_eq!(config.camera.display_surface_z, 2.0);
+        // Keys left out keep their defaults.
+        assert_eq!(config.camera.position, CameraConfig::default().position);
+        assert_eq!(config.background_char, ' ');
+        assert_eq!(parse_config("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(parse_config("rotation_velocity = [0.02, 0.0").is_err());
+        assert!(parse_config("rotation_velocity = \"fast\"").is_err());
+        assert!(parse_config("rotation_speed = [0.0, 0.0, 0.0]").is_err());
+    }
+
+    #[test]
+    fn max_duration_must_be_finite_and_non_negative() {
+        let config = parse_config("max_duration = 1.5").unwrap();
+        assert_eq!(config.max_duration(), Some(Duration::from_millis(1500)));
+        assert_eq!(Config::default().max_duration(), None);
+        assert!(parse_config("max_duration = -1.0").is_err());
+        assert!(parse_config("max_duration = inf").is_err());
+        assert!(parse_config("max_duration = nan").is_err());
+    }
+}
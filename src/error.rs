@@ -0,0 +1,55 @@
+use std::{error::Error, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenError {
+    /// Two screens were combined whose `(width, height)` differ.
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// An intensity ramp needs at least one character.
+    EmptyRamp,
+    /// More faces were given than object ids can tell apart.
+    TooManyFaces { count: usize },
+}
+
+impl fmt::Display for ScreenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenError::DimensionMismatch { expected, found } => write!(
+                f,
+                "screen dimensions differ: expected {}x{}, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            ScreenError::EmptyRamp => write!(f, "intensity ramp is empty"),
+            ScreenError::TooManyFaces { count } => {
+                write!(f, "{} faces do not fit in object ids, at most {} can be drawn", count, u16::MAX)
+            }
+        }
+    }
+}
+
+impl Error for ScreenError {}
+
+/// Why `Screen::try_project` could not place a point on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectError {
+    /// The point is behind the camera or closer than `near_epsilon`.
+    BehindCamera,
+    /// The point projects outside the screen or viewport.
+    OutOfBounds,
+    /// The point or its projection has a NaN or infinite coordinate.
+    NonFinite,
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::BehindCamera => write!(f, "point is behind the camera"),
+            ProjectError::OutOfBounds => write!(f, "point projects outside the screen"),
+            ProjectError::NonFinite => write!(f, "point is not finite"),
+        }
+    }
+}
+
+impl Error for ProjectError {}
@@ -0,0 +1,186 @@
+use nalgebra::Vector3;
+use std::{error::Error, f64::consts::PI, fmt, fs, fs::File, io::BufWriter, path::Path};
+
+use crate::{render_frame, Camera, Color, Screen, CUBE_EDGES, CUBE_VERTICES};
+
+#[derive(Debug)]
+pub enum ExportError {
+    /// An animation needs at least one frame.
+    NoFrames,
+    /// The frames of an animation differ in `(width, height)`.
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    Encoding(png::EncodingError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::NoFrames => write!(f, "no frames to export"),
+            ExportError::DimensionMismatch { expected, found } => write!(
+                f,
+                "frame dimensions differ: expected {}x{}, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            ExportError::Encoding(error) => write!(f, "failed to encode png: {}", error),
+        }
+    }
+}
+
+impl Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExportError::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<png::EncodingError> for ExportError {
+    fn from(error: png::EncodingError) -> Self {
+        ExportError::Encoding(error)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::Encoding(error.into())
+    }
+}
+
+const WHITE: Color = Color::new(255, 255, 255);
+
+impl Screen {
+    /// Rasterizes the screen to 8-bit RGB, drawing each cell as a
+    /// `scale`-pixel square. Set pixels and overlay text are white unless
+    /// colored, intensities are gray levels and the background is black.
+    pub fn to_rgb(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let mut rgb = Vec::with_capacity(self.width * self.height * scale * scale * 3);
+        for y in 0..self.height {
+            let row_start = rgb.len();
+            for x in 0..self.width {
+                let index = x + y * self.width;
+                let color = if self.overlay[index].is_some() {
+                    WHITE
+                } else if self.pixels[index] || self.intensity[index] > 0.0 {
+                    let level = if self.pixels[index] { 1.0 } else { self.tone_level(self.intensity[index]) };
+                    let Color { r, g, b } = self.colors[index].unwrap_or(WHITE);
+                    let scaled = |channel: u8| (channel as f64 * level).round() as u8;
+                    Color::new(scaled(r), scaled(g), scaled(b))
+                } else {
+                    Color::new(0, 0, 0)
+                };
+                for _ in 0..scale {
+                    rgb.extend_from_slice(&[color.r, color.g, color.b]);
+                }
+            }
+            let row_end = rgb.len();
+            for _ in 1..scale {
+                rgb.extend_from_within(row_start..row_end);
+            }
+        }
+        rgb
+    }
+
+    /// Writes the screen to `path` as a PNG of `to_rgb(scale)`, replacing
+    /// any file already there.
+    pub fn save_png(&self, path: impl AsRef<Path>, scale: usize) -> Result<(), ExportError> {
+        let scale = scale.max(1);
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, (self.width * scale) as u32, (self.height * scale) as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgb(scale))?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Renders one full turn of the demo cube in `count` frames and saves
+    /// them as `frame_0000.png`, `frame_0001.png`, ... in `dir`, creating it
+    /// if needed and overwriting earlier dumps.
+    pub fn dump_frames(&mut self, dir: impl AsRef<Path>, count: usize, scale: usize) -> Result<(), ExportError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let camera = Camera::default();
+        for frame in 0..count {
+            let angle = 2.0 * PI * frame as f64 / count as f64;
+            render_frame(self, &CUBE_VERTICES, &CUBE_EDGES, Vector3::new(angle, 0.0, angle), &camera);
+            self.save_png(dir.join(format!("frame_{:04}.png", frame)), scale)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `frames` to `path` as an animated PNG playing at `fps` and looping
+/// forever, keeping every frame's colors exactly.
+pub fn record_apng(path: impl AsRef<Path>, frames: &[Screen], fps: u16) -> Result<(), ExportError> {
+    let first = frames.first().ok_or(ExportError::NoFrames)?;
+    let (width, height) = (first.width, first.height);
+    if let Some(frame) = frames.iter().find(|frame| (frame.width, frame.height) != (width, height)) {
+        return Err(ExportError::DimensionMismatch {
+            expected: (width, height),
+            found: (frame.width, frame.height),
+        });
+    }
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    encoder.set_frame_delay(1, fps.max(1))?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        writer.write_image_data(&frame.to_rgb(1))?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A path in the system temp directory unique to this process and test.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("thrdrender-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn apng_has_the_signature_and_an_animation_chunk() {
+        let frames: Vec<_> = (0..3)
+            .map(|x| {
+                let mut screen = Screen::new(4, 4);
+                screen.set(x, 1, true);
+                screen
+            })
+            .collect();
+        let path = temp_path("animation.png");
+        record_apng(&path, &frames, 10).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(bytes.windows(4).any(|chunk| chunk == b"acTL"));
+        assert!(matches!(record_apng(&path, &[], 10), Err(ExportError::NoFrames)));
+    }
+
+    #[test]
+    fn dump_frames_writes_numbered_pngs() {
+        let dir = temp_path("frames");
+        let mut screen = Screen::new(20, 10);
+        screen.dump_frames(&dir, 3, 1).unwrap();
+        let exists: Vec<_> = (0..4).map(|frame| dir.join(format!("frame_{:04}.png", frame)).exists()).collect();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(exists, [true, true, true, false]);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::ProjectionMode;
+
+/// The byte Ctrl-C sends once `RawInput` has turned off its signal.
+const CTRL_C: u8 = 0x03;
+
+/// Settings the demo lets the user change with single key presses:
+///
+/// - `p` toggles between perspective and orthographic projection
+/// - `q` or Ctrl-C quits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Controls {
+    pub projection: ProjectionMode,
+    pub quit: bool,
+}
+
+impl Controls {
+    /// Applies the key's action, returning false for keys without one.
+    pub fn handle_key(&mut self, key: u8) -> bool {
+        match key {
+            b'p' | b'P' => self.projection = self.projection.toggled(),
+            b'q' | b'Q' | CTRL_C => self.quit = true,
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn p_toggles_the_projection_mode() {
+        let mut controls = Controls::default();
+        assert!(controls.handle_key(b'p'));
+        assert_eq!(controls.projection, ProjectionMode::Orthographic);
+        assert!(controls.handle_key(b'P'));
+        assert_eq!(controls.projection, ProjectionMode::Perspective);
+        assert!(!controls.quit);
+    }
+
+    #[test]
+    fn q_quits_and_other_keys_do_nothing() {
+        let mut controls = Controls::default();
+        assert!(!controls.handle_key(b'x'));
+        assert_eq!(controls, Controls::default());
+        assert!(controls.handle_key(b'q'));
+        assert!(controls.quit);
+    }
+
+    #[test]
+    fn ctrl_c_quits() {
+        let mut controls = Controls::default();
+        assert!(controls.handle_key(0x03));
+        assert!(controls.quit);
+    }
+}
@@ -0,0 +1,1973 @@
+use nalgebra::{Matrix3, RealField, Vector3};
+use std::collections::BTreeMap;
+#[cfg(feature = "terminal")]
+use std::io::{stdout, Write};
+
+mod camera;
+mod color;
+#[cfg(feature = "config")]
+mod config;
+mod error;
+#[cfg(feature = "png")]
+mod export;
+mod input;
+mod mesh;
+mod pen;
+mod ply;
+mod quality;
+mod render;
+mod rng;
+mod scene;
+#[cfg(feature = "terminal")]
+mod terminal;
+mod transform;
+mod wasm;
+
+pub use camera::{Camera, CameraPath, Handedness, OrbitCamera, ParseViewError, ProjectionMode, StandardView};
+pub use color::{Color, ColorSupport};
+#[cfg(feature = "config")]
+pub use config::{parse_config, CameraConfig, Config, ConfigWatcher};
+pub use error::{ProjectError, ScreenError};
+#[cfg(feature = "png")]
+pub use export::{record_apng, ExportError};
+pub use input::Controls;
+pub use mesh::{
+    face_centroid, face_normal, morph, random_sphere_points, stress_mesh, subdivide_edges, subdivided_cube, uv_sphere,
+    Mesh, QuadMesh, CUBE_EDGES, CUBE_FACES, CUBE_VERTICES,
+};
+pub use pen::Pen;
+pub use ply::{load_ply, parse_ply, ColoredMesh, PlyError};
+pub use quality::{AdaptiveQuality, Quality};
+#[cfg(feature = "terminal")]
+pub use render::TerminalRenderer;
+pub use render::{
+    precise_sleep, render_frame, render_frame_supersampled, replay, run_frames, sleep_spinning, Clock, Deadline,
+    FrameStats, Renderer, WriterRenderer, SPIN_THRESHOLD,
+};
+pub use scene::{Node, Scene, SceneObject};
+#[cfg(feature = "terminal")]
+pub use terminal::{
+    clear_terminal, clear_with_fallback, parse_cell_size_report, query_char_aspect, restore_terminal, ClearStrategy,
+    RawInput, DEFAULT_CHAR_ASPECT, FALLBACK_CLEAR,
+};
+pub use transform::Transform;
+pub use wasm::render_frame_to_string;
+
+/// Default characters for intensities from dimmest to brightest.
+pub const RAMP: &str = ".:-=+*#%@";
+
+/// How intensities are turned into characters by `Screen::build`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadeStyle {
+    /// One character per intensity band, taken from the screen's ramp.
+    #[default]
+    Ramp,
+    /// Diagonal hatching: sparse `/` for faint areas, solid `/`, then `X`
+    /// where `\` is crossed in, and `#` for the brightest.
+    Crosshatch,
+}
+
+/// How `fill_faces` treats faces turned away from the camera.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackfaceStyle {
+    /// Back faces are skipped.
+    Cull,
+    /// Back faces are dithered at low coverage in `ghost_char` on top of
+    /// the front faces, ignoring depth, for an X-ray look.
+    Ghost,
+    /// Back faces are filled like any other, leaving the depth test to hide
+    /// them.
+    #[default]
+    Draw,
+}
+
+/// Which characters `Screen::build` may output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// Only 7-bit ASCII, for terminals and logs that cannot take UTF-8.
+    /// Other characters are swapped for a similar-looking ASCII one.
+    AsciiOnly,
+}
+
+/// Characters for a frame drawn around the edge of the built screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BorderChars {
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    pub horizontal: char,
+    pub vertical: char,
+}
+
+impl BorderChars {
+    pub const ASCII: BorderChars = BorderChars {
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        horizontal: '-',
+        vertical: '|',
+    };
+
+    pub const BOX_DRAWING: BorderChars = BorderChars {
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        horizontal: '─',
+        vertical: '│',
+    };
+
+    /// The border character for output cell `(column, row)` of a
+    /// `width` x `height` frame, or `None` inside the border.
+    fn at(&self, column: usize, row: usize, width: usize, height: usize) -> Option<char> {
+        let (left, right) = (column == 0, column + 1 == width);
+        let (top, bottom) = (row == 0, row + 1 == height);
+        match (top, bottom, left, right) {
+            (true, _, true, _) => Some(self.top_left),
+            (true, _, _, true) => Some(self.top_right),
+            (_, true, true, _) => Some(self.bottom_left),
+            (_, true, _, true) => Some(self.bottom_right),
+            (true, ..) | (_, true, ..) => Some(self.horizontal),
+            (_, _, true, _) | (.., true) => Some(self.vertical),
+            _ => None,
+        }
+    }
+}
+
+pub struct Screen {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+    colors: Vec<Option<Color>>,
+    intensity: Vec<f64>,
+    overlay: Vec<Option<char>>,
+    glyphs: Vec<Option<char>>,
+    current_glyph: Option<char>,
+    depth: Vec<f64>,
+    object_ids: Option<Vec<u16>>,
+    ramp: Vec<char>,
+    buffer: String,
+    pub trailing_newline: bool,
+    /// Frames the built output, covering its outermost rows and columns
+    /// without touching the pixel buffers.
+    pub border: Option<BorderChars>,
+    /// Characters for set and unset pixels without intensity.
+    pub pixel_char: char,
+    pub background_char: char,
+    /// Mirror the built frame left-to-right and top-to-bottom respectively.
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// Blends between orthographic (0.0) and full perspective (1.0) projection.
+    pub perspective_strength: f64,
+    /// Where the optical axis meets the screen, in normalized coordinates
+    /// measured from the top-left corner.
+    pub principal_point: (f64, f64),
+    /// Height over width of a terminal cell, as `query_char_aspect` reports.
+    /// When set, a unit spans `char_aspect` times as many columns as rows, so
+    /// shapes keep their proportions whatever the screen size. When unset the
+    /// projection is stretched to fill the viewport in both directions.
+    pub char_aspect: Option<f64>,
+    /// Camera-space depth below which points count as behind the camera,
+    /// keeping points right at the camera from projecting to huge
+    /// coordinates. `draw_mesh` clips edges against this plane.
+    pub near_epsilon: f64,
+    /// Points projecting further than this many screen widths or heights
+    /// from the origin are rejected by `project_3d_point` before they are
+    /// converted to pixels.
+    pub projection_limit: f64,
+    /// The `(x, y, width, height)` rectangle projected meshes are mapped into
+    /// and clipped to, for split-screen views; `None` uses the whole screen.
+    /// Pixel-addressed drawing such as `draw_line` still spans the screen.
+    pub viewport: Option<(usize, usize, usize, usize)>,
+    /// Shifts the projection by a different sub-pixel offset every frame,
+    /// which combined with persistence or supersampling smooths edges.
+    pub jitter_enabled: bool,
+    jitter_index: usize,
+    jitter_offset: (f64, f64),
+    /// Edges spanning fewer pixels than this are skipped by `render_frame`.
+    pub min_line_length: usize,
+    /// Pixels left out at each end of every mesh edge, for a diagram look
+    /// with gaps around the vertices. Edges too short to keep any pixels are
+    /// skipped.
+    pub vertex_gap: usize,
+    /// Pixels drawn and skipped in turn by `draw_line_dashed`.
+    pub dash_pattern: (usize, usize),
+    /// Skips vertices facing away from the camera, along with their edges.
+    pub cull_back_vertices: bool,
+    /// Compresses accumulated intensities with `x / (1 + x)` before they are
+    /// mapped onto the ramp, instead of clamping them to 1.0.
+    pub tone_mapping: bool,
+    pub shade_style: ShadeStyle,
+    /// Colors are reduced to what the terminal can show; see
+    /// `ColorSupport::detect`.
+    pub color_support: ColorSupport,
+    /// Fraction of each pixel's intensity kept from one frame to the next:
+    /// 0.0 clears fully every frame, 1.0 never fades.
+    pub persistence: f64,
+    /// Labels every projected vertex with its index in the text overlay, for
+    /// debugging mesh connectivity.
+    pub inspect: bool,
+    /// Marks every projected vertex with this character in the text
+    /// overlay, on top of the edges.
+    pub vertex_char: Option<char>,
+    /// Id recorded in the object-id buffer for everything drawn; 0 is
+    /// reserved for the background.
+    pub current_object: u16,
+    /// Characters for mesh edges by depth, nearest first: the range of depths
+    /// spanned by each mesh is split into one band per character, and each
+    /// edge is drawn with the character of its midpoint's band. Empty draws
+    /// every edge with `pixel_char`.
+    pub depth_chars: Vec<char>,
+    /// Fill character for each face drawn by `fill_faces`, by face index.
+    /// Faces past the end use `pixel_char`.
+    pub face_materials: Vec<char>,
+    pub backface_style: BackfaceStyle,
+    /// Character for the back faces drawn by `BackfaceStyle::Ghost`.
+    pub ghost_char: char,
+    pub encoding: Encoding,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+            colors: vec![None; width * height],
+            intensity: vec![0.0; width * height],
+            overlay: vec![None; width * height],
+            glyphs: vec![None; width * height],
+            current_glyph: None,
+            depth: vec![f64::INFINITY; width * height],
+            object_ids: None,
+            ramp: RAMP.chars().collect(),
+            buffer: String::with_capacity((width + 1) * height),
+            trailing_newline: true,
+            border: None,
+            pixel_char: '.',
+            background_char: ' ',
+            flip_horizontal: false,
+            flip_vertical: false,
+            perspective_strength: 1.0,
+            principal_point: (0.5, 0.5),
+            char_aspect: None,
+            near_epsilon: 1e-3,
+            projection_limit: 4.0,
+            viewport: None,
+            jitter_enabled: false,
+            jitter_index: 0,
+            jitter_offset: (0.0, 0.0),
+            min_line_length: 0,
+            vertex_gap: 0,
+            dash_pattern: (2, 2),
+            cull_back_vertices: false,
+            tone_mapping: true,
+            shade_style: ShadeStyle::Ramp,
+            color_support: ColorSupport::TrueColor,
+            persistence: 0.0,
+            inspect: false,
+            vertex_char: None,
+            current_object: 1,
+            depth_chars: Vec::new(),
+            face_materials: Vec::new(),
+            backface_style: BackfaceStyle::Draw,
+            ghost_char: '`',
+            encoding: Encoding::Utf8,
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        if x < self.width && y < self.height {
+            let index = x + y * self.width;
+            self.pixels[index] = value;
+            self.colors[index] = None;
+            self.tag_pixel(index, value);
+        }
+    }
+
+    /// Sets the pixel and tints it with `color` when the screen is built.
+    pub fn set_color(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            let index = x + y * self.width;
+            self.pixels[index] = true;
+            self.colors[index] = Some(color);
+            self.tag_pixel(index, true);
+        }
+    }
+
+    /// Starts recording which object drew each pixel, readable through
+    /// `object_id_at` and `contains_point`.
+    pub fn enable_object_ids(&mut self) {
+        if self.object_ids.is_none() {
+            self.object_ids = Some(vec![0; self.width * self.height]);
+        }
+    }
+
+    /// The id of the object that last drew `(x, y)`, or 0 for background.
+    /// `None` if the pixel is off-screen or ids are not being recorded.
+    pub fn object_id_at(&self, x: usize, y: usize) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.object_ids.as_ref().map(|object_ids| object_ids[x + y * self.width])
+    }
+
+    /// Records the current object id and glyph for a pixel being set or
+    /// cleared.
+    fn tag_pixel(&mut self, index: usize, value: bool) {
+        self.glyphs[index] = if value { self.current_glyph } else { None };
+        if let Some(object_ids) = &mut self.object_ids {
+            object_ids[index] = if value { self.current_object } else { 0 };
+        }
+    }
+
+    /// Adds `amount` to the pixel's intensity; values above 1.0 accumulate
+    /// and are tone mapped when the screen is built.
+    pub fn add_intensity(&mut self, x: usize, y: usize, amount: f64) {
+        if x < self.width && y < self.height {
+            self.intensity[x + y * self.width] += amount;
+        }
+    }
+
+    pub fn set_intensity(&mut self, x: usize, y: usize, value: f64) {
+        if x < self.width && y < self.height {
+            self.intensity[x + y * self.width] = value;
+        }
+    }
+
+    pub fn intensity(&self, x: usize, y: usize) -> f64 {
+        if x < self.width && y < self.height {
+            self.intensity[x + y * self.width]
+        } else {
+            0.0
+        }
+    }
+
+    /// Writes `text` into the overlay starting at `(x, y)`, clipped at the
+    /// right edge. Overlay characters are drawn on top of everything else.
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str) {
+        if y >= self.height {
+            return;
+        }
+        for (column, character) in (x..self.width).zip(text.chars()) {
+            self.overlay[column + y * self.width] = Some(character);
+        }
+    }
+
+    pub fn overlay(&self, x: usize, y: usize) -> Option<char> {
+        if x < self.width && y < self.height {
+            self.overlay[x + y * self.width]
+        } else {
+            None
+        }
+    }
+
+    /// The pixels as rows, indexed `[y][x]`.
+    pub fn to_grid(&self) -> Vec<Vec<bool>> {
+        self.pixels.chunks(self.width.max(1)).map(<[bool]>::to_vec).collect()
+    }
+
+    /// Packs the pixels one bit each, most significant bit first, for
+    /// monochrome framebuffers. Every row is padded to a whole byte.
+    pub fn to_packed_bits(&self) -> Vec<u8> {
+        let row_bytes = self.width.div_ceil(8);
+        let mut packed = vec![0; row_bytes * self.height];
+        for (y, row) in self.pixels.chunks(self.width.max(1)).enumerate() {
+            for (x, _) in row.iter().enumerate().filter(|&(_, &pixel)| pixel) {
+                packed[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        packed
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[x + y * self.width]
+    }
+
+    pub fn color(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.width && y < self.height {
+            self.colors[x + y * self.width]
+        } else {
+            None
+        }
+    }
+
+    /// Resets every per-pixel buffer, so nothing from the previous frame
+    /// survives into the next one.
+    pub fn clear(&mut self) {
+        self.pixels.fill(false);
+        self.colors.fill(None);
+        self.intensity.fill(0.0);
+        self.overlay.fill(None);
+        self.glyphs.fill(None);
+        self.depth.fill(f64::INFINITY);
+        if let Some(object_ids) = &mut self.object_ids {
+            object_ids.fill(0);
+        }
+    }
+
+    /// Like `clear`, but fades the intensity buffer by `persistence` instead
+    /// of zeroing it, leaving trails behind moving geometry.
+    pub fn clear_with_persistence(&mut self) {
+        self.pixels.fill(false);
+        self.colors.fill(None);
+        self.overlay.fill(None);
+        self.glyphs.fill(None);
+        self.depth.fill(f64::INFINITY);
+        if let Some(object_ids) = &mut self.object_ids {
+            object_ids.fill(0);
+        }
+        if self.persistence > 0.0 {
+            self.intensity.iter_mut().for_each(|intensity| *intensity *= self.persistence);
+        } else {
+            self.intensity.fill(0.0);
+        }
+    }
+
+    /// Projects a point seen from a camera at `camera_position` looking down
+    /// +z, for any float type.
+    pub fn project_3d_point<T: RealField + Copy>(
+        &mut self,
+        point: Vector3<T>,
+        camera_position: Vector3<T>,
+        display_surface_z: T,
+    ) -> Option<(usize, usize)> {
+        self.project_view_point(point - camera_position, -camera_position.z, display_surface_z)
+    }
+
+    /// Projects a point already in camera space. `reference_z` is the camera
+    /// space depth of the world origin, used by orthographic projection.
+    pub(crate) fn project_view_point<T: RealField + Copy>(
+        &self,
+        transformed_point: Vector3<T>,
+        reference_z: T,
+        display_surface_z: T,
+    ) -> Option<(usize, usize)> {
+        let (x, y) = self.view_to_screen(transformed_point, reference_z, display_surface_z)?;
+        self.pixel_at(x, y)
+    }
+
+    /// The pixel continuous screen coordinates `(x, y)` land on, or `None` if
+    /// they are outside the viewport or `projection_limit`.
+    pub(crate) fn pixel_at(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let within_limit = |value: f64, size: usize| value.abs() <= self.projection_limit * size as f64;
+        if !within_limit(x, self.width) || !within_limit(y, self.height) {
+            return None;
+        }
+        // Rounding to the nearest pixel, rather than truncating, keeps slowly
+        // moving points from snapping unevenly; ties go to even so exact
+        // half-pixel positions do not all shift the same way.
+        let (screen_x, screen_y) = (x.round_ties_even(), y.round_ties_even());
+
+        let (left, top, width, height) = self.viewport_rect();
+        let inside = |value: f64, start: usize, size: usize| value >= start as f64 && value < (start + size) as f64;
+        if inside(screen_x, left, width) && inside(screen_y, top, height) {
+            return Some((screen_x as usize, screen_y as usize));
+        }
+        None
+    }
+
+    /// Whether the bounding box of the camera-space `points` lies entirely
+    /// outside the part of space `project_view_point` maps onto the screen,
+    /// honoring `perspective_strength` and `principal_point`.
+    pub(crate) fn is_outside_view(&self, points: &[Vector3<f64>], reference_z: f64, display_surface_z: f64) -> bool {
+        let Some(&first) = points.first() else {
+            return true;
+        };
+        let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+            (min.inf(point), max.sup(point))
+        });
+
+        // Points are divided by the blended depth `base + slope * z`, which is
+        // affine in z, so the screen edges are still planes, just not ones
+        // through the camera once the projection leans orthographic.
+        let (base, slope) = if reference_z > 0.0 {
+            (reference_z * (1.0 - self.perspective_strength), self.perspective_strength)
+        } else {
+            (0.0, 1.0)
+        };
+        // How far the principal point is from each screen edge, as a fraction
+        // of the viewport, widened by a pixel for rounding and jitter.
+        let (_, _, width, height) = self.viewport_rect();
+        let (principal_x, principal_y) = self.principal_point;
+        let (margin_x, margin_y) = (1.0 / width.max(1) as f64, 1.0 / height.max(1) as f64);
+        // Horizontal fractions are turned into projected units.
+        let stretch = width as f64 / self.columns_per_unit();
+        let [left, right, top, bottom] = [
+            (principal_x + margin_x) * stretch,
+            (1.0 - principal_x + margin_x) * stretch,
+            principal_y + margin_y,
+            1.0 - principal_y + margin_y,
+        ]
+        .map(|extent| 2.0 * extent);
+
+        let d = display_surface_z;
+        let planes = [
+            (Vector3::new(0.0, 0.0, 1.0), 0.0),
+            (Vector3::new(d, 0.0, left * slope), left * base),
+            (Vector3::new(-d, 0.0, right * slope), right * base),
+            (Vector3::new(0.0, -d, top * slope), top * base),
+            (Vector3::new(0.0, d, bottom * slope), bottom * base),
+        ];
+        is_box_outside(min, max, &planes)
+    }
+
+    /// The viewport clamped to the screen, or the whole screen if unset.
+    pub(crate) fn viewport_rect(&self) -> (usize, usize, usize, usize) {
+        let Some((x, y, width, height)) = self.viewport else {
+            return (0, 0, self.width, self.height);
+        };
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        (x, y, width.min(self.width - x), height.min(self.height - y))
+    }
+
+    /// Maps a camera-space point to continuous, unclipped screen coordinates,
+    /// or `None` if it lies behind the camera.
+    pub(crate) fn view_to_screen<T: RealField + Copy>(
+        &self,
+        transformed_point: Vector3<T>,
+        reference_z: T,
+        display_surface_z: T,
+    ) -> Option<(f64, f64)> {
+        let near_epsilon: T = nalgebra::convert(self.near_epsilon);
+        if transformed_point.z <= T::zero() || transformed_point.z < near_epsilon {
+            return None;
+        }
+
+        // Orthographic projection divides every point by the depth of the
+        // world origin; perspective uses the point's own depth.
+        let depth = if reference_z > T::zero() {
+            let strength: T = nalgebra::convert(self.perspective_strength);
+            reference_z + (transformed_point.z - reference_z) * strength
+        } else {
+            transformed_point.z
+        };
+
+        let projected_x: f64 = nalgebra::convert_unchecked((display_surface_z / depth) * transformed_point.x);
+        let projected_y: f64 = nalgebra::convert_unchecked((display_surface_z / depth) * transformed_point.y);
+
+        let (principal_x, principal_y) = self.principal_point;
+        let (jitter_x, jitter_y) = if self.jitter_enabled { self.jitter_offset } else { (0.0, 0.0) };
+        let (left, top, width, height) = self.viewport_rect();
+        Some((
+            principal_x * width as f64 + projected_x * 0.5 * self.columns_per_unit() + left as f64 + jitter_x,
+            (principal_y - projected_y * 0.5) * (height as f64) + top as f64 + jitter_y,
+        ))
+    }
+
+    /// Columns spanned by a projected unit in x, twice the distance from the
+    /// principal point to where x = 1 lands.
+    fn columns_per_unit(&self) -> f64 {
+        let (_, _, width, height) = self.viewport_rect();
+        match self.char_aspect {
+            Some(aspect) => height as f64 * aspect,
+            None => width as f64,
+        }
+    }
+
+    /// Moves on to the next jitter offset, from a (2, 3) Halton sequence
+    /// repeating every `JITTER_PERIOD` frames.
+    pub fn advance_jitter(&mut self) {
+        self.jitter_index = self.jitter_index % JITTER_PERIOD + 1;
+        self.jitter_offset = (
+            halton(self.jitter_index, 2) - 0.5,
+            halton(self.jitter_index, 3) - 0.5,
+        );
+    }
+
+    pub fn draw_line(&mut self, start: (usize, usize), end: (usize, usize)) {
+        walk_line(start, end, |x, y| {
+            if x < self.width && y < self.height {
+                self.set_unchecked(x, y, true);
+            }
+        });
+    }
+
+    /// Like `draw_line`, but alternately drawing and skipping runs of pixels
+    /// as set by `dash_pattern`, starting with a dash at `start`.
+    pub fn draw_line_dashed(&mut self, start: (usize, usize), end: (usize, usize)) {
+        let (dash, gap) = self.dash_pattern;
+        let period = (dash + gap).max(1);
+        let mut step = 0;
+        walk_line(start, end, |x, y| {
+            if step % period < dash && x < self.width && y < self.height {
+                self.set_unchecked(x, y, true);
+            }
+            step += 1;
+        });
+    }
+
+    /// Flips every pixel on or off. If any intensities are in use they are
+    /// inverted too, as `1.0 - value` after clamping to 1.0.
+    pub fn invert(&mut self) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = !*pixel);
+        if self.intensity.iter().any(|&intensity| intensity > 0.0) {
+            self.intensity.iter_mut().for_each(|intensity| *intensity = 1.0 - intensity.min(1.0));
+        }
+    }
+
+    /// Sets the span from `x0` to `x1` (inclusive) on row `y`, clipped to the
+    /// screen, with a single bounds check for the whole span.
+    pub fn set_row(&mut self, y: usize, x0: usize, x1: usize, value: bool) {
+        let (x0, x1) = (x0.min(x1), x0.max(x1));
+        if y >= self.height || x0 >= self.width {
+            return;
+        }
+        let row = y * self.width;
+        let span = row + x0..=row + x1.min(self.width - 1);
+        self.pixels[span.clone()].fill(value);
+        self.colors[span.clone()].fill(None);
+        self.glyphs[span.clone()].fill(if value { self.current_glyph } else { None });
+        if let Some(object_ids) = &mut self.object_ids {
+            object_ids[span].fill(if value { self.current_object } else { 0 });
+        }
+    }
+
+    /// Fills the screen with a checkerboard of `cell`-pixel squares, with the
+    /// square at the top-left corner set. Useful for checking the character
+    /// aspect ratio. A `cell` of 0 is treated as 1.
+    pub fn fill_checkerboard(&mut self, cell: usize) {
+        let cell = cell.max(1);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.set_unchecked(x, y, (x / cell + y / cell).is_multiple_of(2));
+            }
+        }
+    }
+
+    /// Like `set`, for callers that have already bounds-checked `(x, y)`.
+    fn set_unchecked(&mut self, x: usize, y: usize, value: bool) {
+        debug_assert!(x < self.width && y < self.height);
+        let index = x + y * self.width;
+        self.pixels[index] = value;
+        self.colors[index] = None;
+        self.tag_pixel(index, value);
+    }
+
+    /// Draws a circle with the midpoint algorithm, clipped to the screen. A
+    /// filled circle is drawn as horizontal spans between mirrored points.
+    pub fn draw_circle(&mut self, center: (usize, usize), radius: usize, filled: bool) {
+        let (cx, cy) = (center.0 as isize, center.1 as isize);
+        let mut x = radius as isize;
+        let mut y = 0;
+        let mut err = 1 - x;
+
+        while x >= y {
+            for (dx, dy) in [(x, y), (y, x)] {
+                for row in [cy - dy, cy + dy] {
+                    if filled {
+                        self.set_span(row, cx - dx, cx + dx);
+                    } else {
+                        self.set_signed(cx - dx, row);
+                        self.set_signed(cx + dx, row);
+                    }
+                }
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    fn set_signed(&mut self, x: isize, y: isize) {
+        if x >= 0 && y >= 0 {
+            self.set(x as usize, y as usize, true);
+        }
+    }
+
+    /// `set_row` for spans that may start left of the screen.
+    fn set_span(&mut self, y: isize, x0: isize, x1: isize) {
+        if y >= 0 && x1 >= 0 {
+            self.set_row(y as usize, x0.max(0) as usize, x1 as usize, true);
+        }
+    }
+
+    /// Fills the triangle `a`, `b`, `c` one horizontal span at a time.
+    pub fn fill_triangle(&mut self, a: (usize, usize), b: (usize, usize), c: (usize, usize), value: bool) {
+        self.for_each_triangle_span(a, b, c, |screen, y, x0, x1| screen.set_row(y, x0, x1, value));
+    }
+
+    /// Fills the quadrilateral with corners `a`, `b`, `c`, `d` in order.
+    /// Convex quads are filled one span per row; a concave or twisted quad,
+    /// as a non-planar face can project to, is split into two triangles along
+    /// the diagonal through its reflex corner.
+    pub fn fill_quad(
+        &mut self,
+        a: (usize, usize),
+        b: (usize, usize),
+        c: (usize, usize),
+        d: (usize, usize),
+        value: bool,
+    ) {
+        let corners = [a, b, c, d];
+        let point = |i: usize| (corners[i % 4].0 as f64, corners[i % 4].1 as f64);
+        // Cross product of the edges meeting at corner i; its sign says which
+        // way the outline turns there.
+        let turns: [f64; 4] = std::array::from_fn(|i| {
+            let (p, q, r) = (point(i + 3), point(i), point(i + 1));
+            (q.0 - p.0) * (r.1 - q.1) - (q.1 - p.1) * (r.0 - q.0)
+        });
+        let left_turns = turns.iter().filter(|&&turn| turn > 0.0).count();
+        let right_turns = turns.iter().filter(|&&turn| turn < 0.0).count();
+        if left_turns > 0 && right_turns > 0 {
+            // A twisted quad turns both ways twice; any diagonal will do.
+            let reflex = match (left_turns, right_turns) {
+                (1, _) => (0..4).position(|i| turns[i] > 0.0).unwrap_or(0),
+                (_, 1) => (0..4).position(|i| turns[i] < 0.0).unwrap_or(0),
+                _ => 0,
+            };
+            let corner = |offset: usize| corners[(reflex + offset) % 4];
+            self.fill_triangle(corner(0), corner(1), corner(2), value);
+            self.fill_triangle(corner(0), corner(2), corner(3), value);
+            return;
+        }
+
+        let min_y = corners.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let max_y = corners.iter().map(|&(_, y)| y).max().unwrap_or(0).min(self.height.saturating_sub(1));
+        for y in min_y..=max_y {
+            let row = y as f64;
+            let (mut left, mut right) = (f64::INFINITY, f64::NEG_INFINITY);
+            for i in 0..4 {
+                let (p, q) = (point(i), point(i + 1));
+                if (p.1 - row) * (q.1 - row) > 0.0 {
+                    continue;
+                }
+                let crossings = if p.1 == q.1 {
+                    [p.0, q.0]
+                } else {
+                    [p.0 + (q.0 - p.0) * (row - p.1) / (q.1 - p.1); 2]
+                };
+                left = crossings.into_iter().fold(left, f64::min);
+                right = crossings.into_iter().fold(right, f64::max);
+            }
+            if left <= right {
+                self.set_row(y, left.round() as usize, right.round() as usize, value);
+            }
+        }
+    }
+
+    /// Fills the triangle semi-transparently: ordered dithering with a 4x4
+    /// Bayer matrix sets roughly `alpha` of its pixels, evenly spread.
+    pub fn fill_triangle_alpha(&mut self, a: (usize, usize), b: (usize, usize), c: (usize, usize), alpha: f64) {
+        self.for_each_triangle_span(a, b, c, |screen, y, x0, x1| {
+            for x in x0.min(x1)..=x0.max(x1).min(screen.width.saturating_sub(1)) {
+                if alpha > bayer_threshold(x, y) {
+                    screen.set(x, y, true);
+                }
+            }
+        });
+    }
+
+    /// Calls `span(self, y, x0, x1)` for each row the triangle covers; the
+    /// ends may come in either order and may extend past the right edge.
+    fn for_each_triangle_span(
+        &mut self,
+        a: (usize, usize),
+        b: (usize, usize),
+        c: (usize, usize),
+        mut span: impl FnMut(&mut Self, usize, usize, usize),
+    ) {
+        let mut vertices = [a, b, c];
+        vertices.sort_by_key(|&(_, y)| y);
+        let [top, middle, bottom] = vertices;
+
+        // The x coordinate where the edge from `p` to `q` crosses row `y`.
+        let edge_x = |p: (usize, usize), q: (usize, usize), y: usize| {
+            if p.1 == q.1 {
+                return q.0 as f64;
+            }
+            let t = (y as f64 - p.1 as f64) / (q.1 as f64 - p.1 as f64);
+            p.0 as f64 + (q.0 as f64 - p.0 as f64) * t
+        };
+
+        if top.1 == bottom.1 {
+            // A flat triangle is a single span through all three corners.
+            if top.1 < self.height {
+                let (x0, x1) = (top.0.min(middle.0).min(bottom.0), top.0.max(middle.0).max(bottom.0));
+                span(self, top.1, x0, x1);
+            }
+            return;
+        }
+        for y in top.1..=bottom.1.min(self.height.saturating_sub(1)) {
+            let long = edge_x(top, bottom, y);
+            // The short side switches edges below the middle corner; a flat
+            // bottom row stays on the upper edge, which ends at the middle
+            // corner, rather than running along the horizontal one.
+            let short = if y < middle.1 || middle.1 == bottom.1 {
+                edge_x(top, middle, y)
+            } else {
+                edge_x(middle, bottom, y)
+            };
+            span(self, y, long.round() as usize, short.round() as usize);
+        }
+    }
+
+    /// Fills the triangle with corners `(x, y, depth)`, keeping only pixels
+    /// nearer than what the depth buffer already holds and tagging them with
+    /// `current_object`.
+    pub fn fill_triangle_depth(&mut self, a: (usize, usize, f64), b: (usize, usize, f64), c: (usize, usize, f64)) {
+        let to_point = |(x, y, _): (usize, usize, f64)| (x as f64, y as f64);
+        let (pa, pb, pc) = (to_point(a), to_point(b), to_point(c));
+        let edge = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0);
+
+        let area = edge(pa, pb, pc);
+        if area == 0.0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        let min_x = a.0.min(b.0).min(c.0);
+        let max_x = a.0.max(b.0).max(c.0).min(self.width - 1);
+        let min_y = a.1.min(b.1).min(c.1);
+        let max_y = a.1.max(b.1).max(c.1).min(self.height - 1);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = (x as f64, y as f64);
+                // Barycentric weights; all share the sign of `area` inside.
+                let wa = edge(pb, pc, p) / area;
+                let wb = edge(pc, pa, p) / area;
+                let wc = edge(pa, pb, p) / area;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+
+                self.set_depth(x, y, wa * a.2 + wb * b.2 + wc * c.2);
+            }
+        }
+    }
+
+    /// Sets the pixel if `depth` is nearer than what the depth buffer holds
+    /// there, returning whether it was set.
+    pub fn set_depth(&mut self, x: usize, y: usize, depth: f64) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let index = x + y * self.width;
+        if depth >= self.depth[index] {
+            return false;
+        }
+        self.depth[index] = depth;
+        self.pixels[index] = true;
+        self.colors[index] = None;
+        self.tag_pixel(index, true);
+        true
+    }
+
+    /// Returns the id of the nearest object filled at `(x, y)`, if any.
+    /// Requires `enable_object_ids`.
+    pub fn contains_point(&self, x: usize, y: usize) -> Option<u16> {
+        self.object_id_at(x, y).filter(|&id| id != 0)
+    }
+
+    /// Counts the visible pixels of each face drawn by `fill_faces`, keyed by
+    /// object id: face index plus one. Other objects are counted by their
+    /// ids the same way; the background is left out. Empty unless
+    /// `enable_object_ids` was called.
+    pub fn face_pixel_counts(&self) -> BTreeMap<u16, usize> {
+        let mut counts = BTreeMap::new();
+        for &id in self.object_ids.iter().flatten().filter(|&&id| id != 0) {
+            *counts.entry(id).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The fraction of pixels that differ from `other`, from 0.0 for
+    /// identical screens to 1.0 when every pixel differs. A pixel differs if
+    /// it is set in only one screen or its intensity, color, glyph or
+    /// overlay text changed.
+    pub fn diff_ratio(&self, other: &Screen) -> Result<f64, ScreenError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(ScreenError::DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+        if self.pixels.is_empty() {
+            return Ok(0.0);
+        }
+
+        let differs = |index: usize| {
+            self.pixels[index] != other.pixels[index]
+                || self.intensity[index] != other.intensity[index]
+                || self.colors[index] != other.colors[index]
+                || self.glyphs[index] != other.glyphs[index]
+                || self.overlay[index] != other.overlay[index]
+        };
+        let changed = (0..self.pixels.len()).filter(|&index| differs(index)).count();
+        Ok(changed as f64 / self.pixels.len() as f64)
+    }
+
+    /// Composites `other` on top of this screen: its set pixels are ORed in,
+    /// its colors, glyphs and overlay text replace ours and the brighter
+    /// intensity wins.
+    pub fn blend(&mut self, other: &Screen) -> Result<(), ScreenError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(ScreenError::DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (other.width, other.height),
+            });
+        }
+
+        for (index, &pixel) in other.pixels.iter().enumerate() {
+            if pixel {
+                self.pixels[index] = true;
+                self.glyphs[index] = other.glyphs[index];
+            }
+            if (pixel || other.intensity[index] > 0.0) && other.colors[index].is_some() {
+                self.colors[index] = other.colors[index];
+            }
+            self.intensity[index] = self.intensity[index].max(other.intensity[index]);
+            if other.overlay[index].is_some() {
+                self.overlay[index] = other.overlay[index];
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `draw_line`, but blends linearly from `start_color` to `end_color`
+    /// along the walk.
+    pub fn draw_line_colored(
+        &mut self,
+        start: (usize, usize),
+        end: (usize, usize),
+        start_color: Color,
+        end_color: Color,
+    ) {
+        let steps = start.0.abs_diff(end.0).max(start.1.abs_diff(end.1)).max(1) as f64;
+        walk_line(start, end, |x, y| {
+            // Every step advances one pixel along the major axis.
+            let step = x.abs_diff(start.0).max(y.abs_diff(start.1));
+            self.set_color(x, y, start_color.lerp(end_color, step as f64 / steps));
+        });
+    }
+
+    pub fn build(&mut self) {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        // Enough for a frame of single-byte characters, so that the common
+        // case never reallocates.
+        buffer.reserve(self.height * (self.width + 1));
+        // Color escapes are only worked out per cell if any could be written.
+        let colored = self.color_support != ColorSupport::Monochrome && self.colors.iter().any(Option::is_some);
+        for row in 0..self.height {
+            let y = if self.flip_vertical { self.height - 1 - row } else { row };
+            let mut current_color = None;
+            for column in 0..self.width {
+                let x = if self.flip_horizontal { self.width - 1 - column } else { column };
+                let index = x + y * self.width;
+                let border = self.border.and_then(|border| border.at(column, row, self.width, self.height));
+                let overlay = border.or(self.overlay[index]);
+                if colored {
+                    let visible = self.pixels[index] || self.intensity[index] > 0.0;
+                    let color = if visible && overlay.is_none() { self.colors[index] } else { None };
+                    if color != current_color {
+                        match color {
+                            Some(color) => color.push_escape(&mut buffer, self.color_support),
+                            None => buffer.push_str(color::RESET),
+                        }
+                        current_color = color;
+                    }
+                }
+                buffer.push(self.cell_char(x, y, overlay));
+            }
+            if current_color.is_some() {
+                buffer.push_str(color::RESET);
+            }
+            // Without the final newline a frame exactly as tall as the terminal
+            // does not scroll it.
+            if self.trailing_newline || row + 1 < self.height {
+                buffer.push('\n');
+            }
+        }
+        self.buffer = buffer;
+    }
+
+    /// Builds just the `width` x `height` block of pixels at `(x, y)`, clipped
+    /// to the screen, as rows of plain characters joined by newlines. Colors,
+    /// flips and the border are left out.
+    pub fn region_build(&self, x: usize, y: usize, width: usize, height: usize) -> String {
+        let columns = x.min(self.width)..x.saturating_add(width).min(self.width);
+        let rows = y.min(self.height)..y.saturating_add(height).min(self.height);
+        let mut region = String::with_capacity((columns.len() + 1) * rows.len());
+        for row in rows.clone() {
+            if row > rows.start {
+                region.push('\n');
+            }
+            for column in columns.clone() {
+                region.push(self.cell_char(column, row, self.overlay[column + row * self.width]));
+            }
+        }
+        region
+    }
+
+    /// The character shown for the pixel at `(x, y)`, with `overlay` taking
+    /// priority over the pixel's own contents, reduced to `encoding`.
+    fn cell_char(&self, x: usize, y: usize, overlay: Option<char>) -> char {
+        let index = x + y * self.width;
+        let intensity = self.intensity[index];
+        let character = if let Some(character) = overlay {
+            character
+        } else if intensity > 0.0 {
+            self.shade_char(x, y, intensity)
+        } else if self.pixels[index] {
+            self.glyphs[index].unwrap_or(self.pixel_char)
+        } else {
+            self.background_char
+        };
+        match self.encoding {
+            Encoding::Utf8 => character,
+            Encoding::AsciiOnly => ascii_fallback(character),
+        }
+    }
+
+    /// Replaces the characters intensities are drawn with, dimmest first.
+    /// Each character covers an equal share of the intensity range.
+    pub fn set_ramp(&mut self, ramp: &str) -> Result<(), ScreenError> {
+        if ramp.is_empty() {
+            return Err(ScreenError::EmptyRamp);
+        }
+        self.ramp = ramp.chars().collect();
+        Ok(())
+    }
+
+    pub fn ramp(&self) -> &[char] {
+        &self.ramp
+    }
+
+    /// Maps an accumulated intensity into 0.0..=1.0, tone mapped or clamped.
+    pub(crate) fn tone_level(&self, intensity: f64) -> f64 {
+        if self.tone_mapping {
+            intensity / (1.0 + intensity)
+        } else {
+            intensity.min(1.0)
+        }
+    }
+
+    fn shade_char(&self, x: usize, y: usize, intensity: f64) -> char {
+        let level = self.tone_level(intensity);
+        match self.shade_style {
+            ShadeStyle::Ramp => {
+                let index = (level * self.ramp.len() as f64) as usize;
+                self.ramp[index.min(self.ramp.len() - 1)]
+            }
+            ShadeStyle::Crosshatch => match (level * 4.0) as usize {
+                0 if (x + y).is_multiple_of(2) => '/',
+                0 => self.background_char,
+                1 => '/',
+                2 => 'X',
+                _ => '#',
+            },
+        }
+    }
+
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Moves the last built frame out, leaving an empty buffer for the next
+    /// `build`. Hand the string back with `set_buffer` once done with it to
+    /// reuse its allocation.
+    pub fn take_buffer(&mut self) -> String {
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Replaces the frame buffer with `buffer`, whose allocation the next
+    /// `build` reuses after clearing it.
+    pub fn set_buffer(&mut self, buffer: String) {
+        self.buffer = buffer;
+    }
+
+    /// FNV-1a hash of the last built frame, stable across platforms and Rust
+    /// versions so it can be pinned in regression tests.
+    pub fn frame_hash(&self) -> u64 {
+        self.buffer.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    /// Writes the built frame over the previous one in a single write.
+    #[cfg(feature = "terminal")]
+    pub fn render(&self) {
+        let mut out = stdout().lock();
+        out.write_all(format!("\x1b[H{}", self.buffer).as_bytes()).unwrap();
+        out.flush().unwrap();
+    }
+}
+
+impl Default for Screen {
+    /// An 80x40 screen, which fits a standard 80-column terminal.
+    fn default() -> Self {
+        Self::new(80, 40)
+    }
+}
+
+/// Calls `visit` for every pixel on the Bresenham line from `start` to `end`,
+/// inclusive and in order. Pixels are not clipped, so off-screen coordinates
+/// are passed on as well.
+pub fn walk_line(start: (usize, usize), end: (usize, usize), mut visit: impl FnMut(usize, usize)) {
+    if start == end {
+        visit(start.0, start.1);
+        return;
+    }
+
+    let (x0, y0) = start;
+    let (x1, y1) = end;
+
+    let dx = (x1 as isize - x0 as isize).abs();
+    let dy = (y1 as isize - y0 as isize).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = if dx > dy { dx } else { -dy } / 2;
+
+    let mut x = x0 as isize;
+    let mut y = y0 as isize;
+
+    loop {
+        // The walk stays between the endpoints, so it never goes negative.
+        visit(x as usize, y as usize);
+        if x == x1 as isize && y == y1 as isize {
+            break;
+        }
+        let e2 = err;
+        if e2 > -dx {
+            err -= dy;
+            x += sx;
+        }
+        if e2 < dy {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// Number of frames before the jitter offsets repeat.
+pub const JITTER_PERIOD: usize = 16;
+
+/// The `index`-th element of the Halton low-discrepancy sequence in `base`,
+/// i.e. the digits of `index` mirrored about the radix point. Index 0 gives
+/// 0.0; base 2 continues 0.5, 0.25, 0.75, ...
+pub fn halton(mut index: usize, base: usize) -> f64 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f64;
+        result += fraction * (index % base) as f64;
+        index /= base;
+    }
+    result
+}
+
+/// The closest ASCII stand-in for `character`: lines for box drawing, a
+/// density-matched ramp character for braille and blocks, and `#` for
+/// anything else that is not ASCII already.
+fn ascii_fallback(character: char) -> char {
+    match character {
+        _ if character.is_ascii() => character,
+        '─' | '━' | '═' | '╌' | '┄' => '-',
+        '│' | '┃' | '║' | '╎' | '┆' => '|',
+        '\u{2500}'..='\u{257f}' => '+',
+        '░' => ':',
+        '▒' => '+',
+        '▓' | '█' | '\u{2580}'..='\u{259f}' => '#',
+        '·' | '•' => '.',
+        '\u{2800}'..='\u{28ff}' => match (character as u32 - 0x2800).count_ones() {
+            0 => ' ',
+            1..=2 => '.',
+            3..=4 => ':',
+            5..=6 => '+',
+            _ => '#',
+        },
+        _ => '#',
+    }
+}
+
+/// The ordered-dithering threshold in `(0, 1)` for pixel `(x, y)`.
+fn bayer_threshold(x: usize, y: usize) -> f64 {
+    const BAYER: [[u8; 4]; 4] = [
+        [0, 8, 2, 10],
+        [12, 4, 14, 6],
+        [3, 11, 1, 9],
+        [15, 7, 13, 5],
+    ];
+    (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0
+}
+
+/// The order in which Euler angle matrices are multiplied: `Xyz` builds
+/// `Rx * Ry * Rz`, so the z rotation is applied to the point first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationOrder {
+    #[default]
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+/// Rotates `point` by the Euler angles `rotation` in `RotationOrder::Xyz`. Like
+/// `Screen::project_3d_point` it works on `f32` as well as `f64`; the mesh
+/// and camera paths built on them, such as `render_frame` and `Camera`, are
+/// `f64` only.
+pub fn rotate_point<T: RealField + Copy>(point: Vector3<T>, rotation: Vector3<T>) -> Vector3<T> {
+    rotate_point_ordered(point, rotation, RotationOrder::Xyz)
+}
+
+/// Like `rotate_point`, composing the per-axis rotations in `order`.
+pub fn rotate_point_ordered<T: RealField + Copy>(
+    point: Vector3<T>,
+    rotation: Vector3<T>,
+    order: RotationOrder,
+) -> Vector3<T> {
+    rotation_matrix(rotation, order) * point
+}
+
+pub(crate) fn rotation_matrix<T: RealField + Copy>(rotation: Vector3<T>, order: RotationOrder) -> Matrix3<T> {
+    let (zero, one) = (T::zero(), T::one());
+
+    let rotation_x = Matrix3::new(
+        one, zero, zero,
+        zero, rotation.x.cos(), -rotation.x.sin(),
+        zero, rotation.x.sin(), rotation.x.cos(),
+    );
+
+    let rotation_y = Matrix3::new(
+        rotation.y.cos(), zero, rotation.y.sin(),
+        zero, one, zero,
+        -rotation.y.sin(), zero, rotation.y.cos(),
+    );
+
+    let rotation_z = Matrix3::new(
+        rotation.z.cos(), -rotation.z.sin(), zero,
+        rotation.z.sin(), rotation.z.cos(), zero,
+        zero, zero, one,
+    );
+
+    match order {
+        RotationOrder::Xyz => rotation_x * rotation_y * rotation_z,
+        RotationOrder::Xzy => rotation_x * rotation_z * rotation_y,
+        RotationOrder::Yxz => rotation_y * rotation_x * rotation_z,
+        RotationOrder::Yzx => rotation_y * rotation_z * rotation_x,
+        RotationOrder::Zxy => rotation_z * rotation_x * rotation_y,
+        RotationOrder::Zyx => rotation_z * rotation_y * rotation_x,
+    }
+}
+
+/// Whether the box from `min` to `max` lies entirely on the outer side of one
+/// of `planes`, each given as an inward normal `n` and offset `d` with the
+/// inside being `n . p + d >= 0`.
+fn is_box_outside<T: RealField + Copy>(min: Vector3<T>, max: Vector3<T>, planes: &[(Vector3<T>, T)]) -> bool {
+    let zero = T::zero();
+    planes.iter().any(|&(normal, offset)| {
+        // The box corner furthest along the normal; if even that one is behind
+        // the plane, the whole box is.
+        let corner = Vector3::new(
+            if normal.x >= zero { max.x } else { min.x },
+            if normal.y >= zero { max.y } else { min.y },
+            if normal.z >= zero { max.z } else { min.z },
+        );
+        normal.dot(&corner) + offset < zero
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(screen: &Screen, y: usize) -> &[bool] {
+        &screen.pixels[y * screen.width..(y + 1) * screen.width]
+    }
+
+    /// `Screen::build` as it was before it pre-sized its buffer and skipped
+    /// the color checks for frames without colors, to pin its output.
+    fn reference_build(screen: &Screen) -> String {
+        let mut buffer = String::new();
+        for row in 0..screen.height {
+            let y = if screen.flip_vertical { screen.height - 1 - row } else { row };
+            let mut current_color = None;
+            for column in 0..screen.width {
+                let x = if screen.flip_horizontal { screen.width - 1 - column } else { column };
+                let index = x + y * screen.width;
+                let border = screen.border.and_then(|border| border.at(column, row, screen.width, screen.height));
+                let overlay = border.or(screen.overlay[index]);
+                let visible = screen.pixels[index] || screen.intensity[index] > 0.0;
+                let colored = visible && overlay.is_none() && screen.color_support != ColorSupport::Monochrome;
+                let color = if colored { screen.colors[index] } else { None };
+                if color != current_color {
+                    match color {
+                        Some(color) => color.push_escape(&mut buffer, screen.color_support),
+                        None => buffer.push_str(color::RESET),
+                    }
+                    current_color = color;
+                }
+                buffer.push(screen.cell_char(x, y, overlay));
+            }
+            if current_color.is_some() {
+                buffer.push_str(color::RESET);
+            }
+            if screen.trailing_newline || row + 1 < screen.height {
+                buffer.push('\n');
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn build_without_trailing_newline_fills_the_frame_exactly() {
+        let mut screen = Screen::new(7, 4);
+        screen.trailing_newline = false;
+        screen.build();
+        assert_eq!(screen.buffer.chars().count(), 4 * 7 + (4 - 1));
+        assert!(!screen.buffer.ends_with('\n'));
+
+        screen.trailing_newline = true;
+        screen.build();
+        assert_eq!(screen.buffer.chars().count(), 4 * (7 + 1));
+    }
+
+    #[test]
+    fn frustum_culls_objects_beside_the_view_only() {
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let culled = |x: f64| {
+            let transform = Transform {
+                translation: Vector3::new(x, 0.0, 0.0),
+                ..Transform::default()
+            };
+            let stats = Screen::new(80, 40).draw_mesh(&CUBE_VERTICES, &CUBE_EDGES, &transform, &camera);
+            stats.objects_culled
+        };
+        assert_eq!(culled(0.0), 0);
+        assert_eq!(culled(50.0), 1);
+        assert_eq!(culled(-50.0), 1);
+    }
+
+    #[test]
+    fn blend_copies_set_pixels_onto_a_blank_screen() {
+        let mut base = Screen::new(4, 3);
+        let mut top = Screen::new(4, 3);
+        top.set_color(2, 1, Color::new(0, 255, 0));
+        base.blend(&top).unwrap();
+        assert!(base.get(2, 1));
+        assert_eq!(base.color(2, 1), Some(Color::new(0, 255, 0)));
+        assert_eq!(base.pixels.iter().filter(|&&pixel| pixel).count(), 1);
+
+        assert_eq!(
+            base.blend(&Screen::new(3, 3)),
+            Err(ScreenError::DimensionMismatch { expected: (4, 3), found: (3, 3) })
+        );
+    }
+
+    #[test]
+    fn perspective_strength_blends_between_orthographic_and_perspective() {
+        let mut screen = Screen::new(100, 100);
+        let point = Vector3::new(1.0, 0.0, 10.0);
+        // Orthographic divides by the origin's depth of 5, perspective by the
+        // point's own depth of 10.
+        let camera_position = Vector3::new(0.0, 0.0, -5.0);
+        screen.perspective_strength = 0.0;
+        assert_eq!(screen.project_3d_point(point + camera_position, camera_position, 1.0), Some((60, 50)));
+        screen.perspective_strength = 1.0;
+        assert_eq!(screen.project_3d_point(point + camera_position, camera_position, 1.0), Some((55, 50)));
+    }
+
+    #[test]
+    fn principal_point_moves_the_projected_center() {
+        let mut screen = Screen::new(80, 40);
+        let camera_position = Vector3::new(0.0, 0.0, -5.0);
+        assert_eq!(screen.project_3d_point(Vector3::zeros(), camera_position, 1.0), Some((40, 20)));
+        screen.principal_point = (0.25, 0.75);
+        assert_eq!(screen.project_3d_point(Vector3::zeros(), camera_position, 1.0), Some((20, 30)));
+    }
+
+    #[test]
+    fn default_screen_is_80_by_40() {
+        let screen = Screen::default();
+        assert_eq!((screen.width(), screen.height()), (80, 40));
+        assert_eq!(screen.pixels.len(), 80 * 40);
+    }
+
+    #[test]
+    fn f32_point_transforms_match_f64() {
+        let mut screen = Screen::new(80, 40);
+        let (point, rotation) = (Vector3::new(1.0, -1.0, 1.0), Vector3::new(0.7, 0.2, 1.3));
+        let rotated = rotate_point(point, rotation);
+        let rotated_f32 = rotate_point(point.cast::<f32>(), rotation.cast::<f32>());
+        assert!((rotated_f32.cast::<f64>() - rotated).norm() < 1e-5);
+
+        let camera_position = Vector3::new(0.0, 2.0, -5.0);
+        let projected = screen.project_3d_point(rotated, camera_position, 1.0);
+        assert!(projected.is_some());
+        assert_eq!(screen.project_3d_point(rotated_f32, camera_position.cast::<f32>(), 1.0), projected);
+    }
+
+    #[test]
+    fn tone_mapping_compresses_bright_intensities() {
+        let mut screen = Screen::new(3, 1);
+        screen.set_intensity(0, 0, 3.0);
+        screen.set_intensity(1, 0, 1e9);
+        let brightest = *screen.ramp().last().unwrap();
+        screen.build();
+        let row: Vec<char> = screen.buffer().chars().collect();
+        assert_ne!(row[0], brightest);
+        assert_eq!(row[1], brightest);
+
+        screen.tone_mapping = false;
+        screen.build();
+        assert_eq!(screen.buffer().chars().next(), Some(brightest));
+    }
+
+    #[test]
+    fn set_row_fills_the_span_and_clips_to_the_width() {
+        let mut screen = Screen::new(8, 3);
+        screen.set_row(1, 2, 4, true);
+        assert_eq!(row(&screen, 1), [false, false, true, true, true, false, false, false]);
+
+        screen.set_row(2, 14, 5, true);
+        assert_eq!(row(&screen, 2), [false, false, false, false, false, true, true, true]);
+        // Rows and spans entirely off screen are ignored.
+        screen.set_row(3, 0, 7, true);
+        screen.set_row(0, 8, 10, true);
+        assert!(row(&screen, 0).iter().all(|&pixel| !pixel));
+    }
+
+    #[test]
+    fn triangle_fill_covers_flat_top_and_bottom_rows() {
+        let mut screen = Screen::new(8, 8);
+        screen.fill_triangle((0, 0), (7, 0), (7, 7), true);
+        screen.fill_triangle((0, 0), (0, 7), (7, 7), true);
+        assert!(screen.pixels.iter().all(|&pixel| pixel));
+
+        screen.clear();
+        screen.fill_triangle((6, 3), (1, 3), (3, 3), true);
+        assert_eq!(row(&screen, 3), [false, true, true, true, true, true, true, false]);
+    }
+
+    #[test]
+    fn flips_mirror_the_built_frame() {
+        let mut screen = Screen::new(4, 2);
+        screen.trailing_newline = false;
+        screen.pixel_char = '#';
+        screen.set(0, 0, true);
+
+        screen.flip_horizontal = true;
+        screen.build();
+        assert_eq!(screen.buffer(), "   #\n    ");
+
+        screen.flip_vertical = true;
+        screen.build();
+        assert_eq!(screen.buffer(), "    \n   #");
+        // Flipping only changes the output, not the pixels.
+        assert!(screen.get(0, 0));
+    }
+
+    #[test]
+    fn clear_resets_every_buffer() {
+        let mut screen = Screen::new(4, 3);
+        screen.enable_object_ids();
+        screen.current_object = 7;
+        screen.current_glyph = Some('*');
+        screen.set_intensity(0, 0, 0.5);
+        screen.set_color(1, 0, Color::new(255, 0, 0));
+        screen.draw_text(0, 1, "hi");
+        screen.set_depth(2, 2, 3.0);
+        screen.set(3, 2, true);
+
+        screen.clear();
+        let fresh = Screen::new(4, 3);
+        assert_eq!(screen.pixels, fresh.pixels);
+        assert_eq!(screen.intensity, fresh.intensity);
+        assert_eq!(screen.colors, fresh.colors);
+        assert_eq!(screen.overlay, fresh.overlay);
+        assert_eq!(screen.glyphs, fresh.glyphs);
+        assert_eq!(screen.depth, fresh.depth);
+        assert_eq!(screen.object_ids, Some(vec![0; 4 * 3]));
+        assert_eq!(screen.diff_ratio(&fresh), Ok(0.0));
+    }
+
+    #[test]
+    fn two_character_ramp_splits_at_one_half() {
+        let mut screen = Screen::new(4, 1);
+        screen.trailing_newline = false;
+        screen.tone_mapping = false;
+        screen.set_ramp("lh").unwrap();
+        for (x, intensity) in [0.1, 0.49, 0.5, 0.9].into_iter().enumerate() {
+            screen.set_intensity(x, 0, intensity);
+        }
+        screen.build();
+        assert_eq!(screen.buffer(), "llhh");
+        assert_eq!(screen.set_ramp(""), Err(ScreenError::EmptyRamp));
+        assert_eq!(screen.ramp(), ['l', 'h']);
+    }
+
+    #[test]
+    fn persistence_fades_intensity_on_clear() {
+        let mut screen = Screen::new(2, 1);
+        screen.persistence = 0.5;
+        screen.set(0, 0, true);
+        screen.set_intensity(0, 0, 1.0);
+        screen.clear_with_persistence();
+        assert_eq!(screen.intensity(0, 0), 0.5);
+        assert!(!screen.get(0, 0));
+
+        screen.persistence = 0.0;
+        screen.clear_with_persistence();
+        assert_eq!(screen.intensity(0, 0), 0.0);
+    }
+
+    #[test]
+    fn contains_point_picks_the_nearer_object() {
+        let near = [(0, 0, 2.0), (12, 0, 2.0), (0, 9, 2.0)];
+        let far = [(3, 0, 5.0), (19, 0, 5.0), (19, 9, 5.0)];
+        // Whichever order they are drawn in, the overlap goes to the nearer.
+        for (first, second) in [((1, near), (2, far)), ((2, far), (1, near))] {
+            let mut screen = Screen::new(20, 10);
+            screen.enable_object_ids();
+            for (id, [a, b, c]) in [first, second] {
+                screen.current_object = id;
+                screen.fill_triangle_depth(a, b, c);
+            }
+            assert_eq!(screen.contains_point(4, 1), Some(1));
+            assert_eq!(screen.contains_point(17, 5), Some(2));
+            assert_eq!(screen.contains_point(1, 8), Some(1));
+            assert_eq!(screen.contains_point(10, 9), None);
+        }
+    }
+
+    #[test]
+    fn object_ids_record_who_drew_each_pixel() {
+        let mut screen = Screen::new(10, 4);
+        screen.set(0, 0, true);
+        assert_eq!(screen.object_id_at(0, 0), None);
+
+        screen.enable_object_ids();
+        screen.current_object = 1;
+        screen.draw_line((0, 1), (9, 1));
+        screen.current_object = 2;
+        screen.draw_line((5, 0), (5, 3));
+        assert_eq!(screen.object_id_at(2, 1), Some(1));
+        assert_eq!(screen.object_id_at(5, 1), Some(2));
+        assert_eq!(screen.object_id_at(5, 3), Some(2));
+        assert_eq!(screen.object_id_at(2, 3), Some(0));
+        assert_eq!(screen.object_id_at(10, 0), None);
+
+        screen.set(5, 3, false);
+        assert_eq!(screen.object_id_at(5, 3), Some(0));
+    }
+
+    #[test]
+    fn grid_is_indexed_by_row_then_column() {
+        let mut screen = Screen::new(5, 3);
+        screen.set(4, 1, true);
+        let grid = screen.to_grid();
+        assert_eq!(grid.len(), 3);
+        assert!(grid.iter().all(|row| row.len() == 5));
+        assert!(grid[1][4]);
+        assert_eq!(grid.iter().flatten().filter(|&&pixel| pixel).count(), 1);
+    }
+
+    #[test]
+    fn frame_hash_is_stable_for_equal_frames() {
+        let hash = |angle: f64| {
+            let mut screen = Screen::new(80, 40);
+            render_frame(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, Vector3::new(angle, 0.0, angle), &Camera::default());
+            screen.frame_hash()
+        };
+        assert_eq!(hash(0.5), hash(0.5));
+        assert_ne!(hash(0.5), hash(0.6));
+        // FNV-1a of no bytes is its offset basis.
+        assert_eq!(Screen::new(0, 0).frame_hash(), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn points_nearer_than_the_epsilon_are_rejected() {
+        let mut screen = Screen::new(80, 40);
+        screen.projection_limit = f64::INFINITY;
+        assert_eq!(screen.project_view_point(Vector3::new(0.0, 0.0, 0.0), 0.0, 1.0), None);
+        assert_eq!(screen.project_view_point(Vector3::new(0.0, 0.0, 1e-12), 0.0, 1.0), None);
+        assert_eq!(screen.project_view_point(Vector3::new(0.0, 0.0, 0.01), 0.0, 1.0), Some((40, 20)));
+
+        screen.near_epsilon = 0.1;
+        assert_eq!(screen.project_view_point(Vector3::new(0.0, 0.0, 0.01), 0.0, 1.0), None);
+    }
+
+    #[test]
+    fn circle_outline_leaves_the_center_empty() {
+        let mut screen = Screen::new(20, 20);
+        screen.draw_circle((10, 10), 5, false);
+        let count = screen.to_grid().iter().flatten().filter(|&&pixel| pixel).count();
+        // About 2 * PI * 5 pixels, give or take the octant seams.
+        assert!((26..=36).contains(&count), "{}", count);
+        assert!(!screen.get(10, 10));
+        assert!(screen.get(15, 10) && screen.get(5, 10) && screen.get(10, 5) && screen.get(10, 15));
+
+        screen.draw_circle((10, 10), 5, true);
+        assert!(screen.get(10, 10));
+        // Circles past the top-left corner are clipped rather than wrapping.
+        screen.clear();
+        screen.draw_circle((0, 0), 3, true);
+        assert!(screen.get(0, 0) && !screen.get(19, 19));
+    }
+
+    #[test]
+    fn half_alpha_dithers_into_a_checkerboard() {
+        // A triangle large enough to cover the whole screen.
+        let mut screen = Screen::new(8, 8);
+        screen.fill_triangle_alpha((0, 0), (16, 0), (0, 16), 0.5);
+        let grid = screen.to_grid();
+        assert_eq!(grid.iter().flatten().filter(|&&pixel| pixel).count(), 32);
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &pixel) in row.iter().enumerate() {
+                assert_eq!(pixel, (x + y) % 2 == 0, "({}, {})", x, y);
+            }
+        }
+
+        screen.clear();
+        screen.fill_triangle_alpha((0, 0), (16, 0), (0, 16), 0.0);
+        assert!(screen.to_grid().iter().flatten().all(|&pixel| !pixel));
+    }
+
+    #[test]
+    fn halton_base_two_mirrors_the_binary_digits() {
+        assert_eq!(halton(0, 2), 0.0);
+        assert_eq!([1, 2, 3].map(|index| halton(index, 2)), [0.5, 0.25, 0.75]);
+        assert_eq!([1, 2, 3].map(|index| halton(index, 3)), [1.0 / 3.0, 2.0 / 3.0, 1.0 / 9.0]);
+    }
+
+    #[test]
+    fn jitter_offsets_stay_within_half_a_pixel_and_repeat() {
+        let mut screen = Screen::new(8, 8);
+        let offsets: Vec<_> = (0..2 * JITTER_PERIOD)
+            .map(|_| {
+                screen.advance_jitter();
+                screen.jitter_offset
+            })
+            .collect();
+        assert!(offsets.iter().all(|&(x, y)| x.abs() < 0.5 && y.abs() < 0.5));
+        assert_eq!(offsets[..JITTER_PERIOD], offsets[JITTER_PERIOD..]);
+        assert_eq!(offsets[0], (0.0, 1.0 / 3.0 - 0.5));
+    }
+
+    #[test]
+    fn packs_pixels_msb_first_with_padded_rows() {
+        let mut screen = Screen::new(10, 2);
+        for (x, y) in [(0, 0), (7, 0), (8, 0), (1, 1), (9, 1)] {
+            screen.set(x, y, true);
+        }
+        assert_eq!(screen.to_packed_bits(), [0b1000_0001, 0b1000_0000, 0b0100_0000, 0b0100_0000]);
+        assert!(Screen::new(0, 3).to_packed_bits().is_empty());
+    }
+
+    #[test]
+    fn rotation_order_sets_the_matrix_composition() {
+        let (point, rotation) = (Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.4, -0.9, 1.3));
+        let zyx = rotate_point_ordered(point, rotation, RotationOrder::Zyx);
+        let xyz = rotate_point_ordered(point, rotation, RotationOrder::Xyz);
+        assert!((zyx - xyz).norm() > 0.1);
+        assert_eq!(rotate_point(point, rotation), xyz);
+
+        let about = |axis, angle| nalgebra::Rotation3::from_axis_angle(&axis, angle);
+        let (x, y, z) = (
+            about(Vector3::x_axis(), rotation.x),
+            about(Vector3::y_axis(), rotation.y),
+            about(Vector3::z_axis(), rotation.z),
+        );
+        assert!((xyz - x * y * z * point).norm() < 1e-12);
+        assert!((zyx - z * y * x * point).norm() < 1e-12);
+    }
+
+    #[test]
+    fn crosshatch_gets_denser_with_intensity() {
+        let mut screen = Screen::new(4, 2);
+        screen.trailing_newline = false;
+        screen.shade_style = ShadeStyle::Crosshatch;
+        screen.tone_mapping = false;
+        for x in 0..4 {
+            screen.set_intensity(x, 0, 0.1);
+            screen.set_intensity(x, 1, 1.0);
+        }
+        // Faint areas only hatch every other cell; the brightest are solid.
+        screen.build();
+        assert_eq!(screen.buffer(), "/ / \n####");
+
+        screen.set_intensity(0, 0, 0.3);
+        screen.set_intensity(1, 0, 0.6);
+        screen.build();
+        assert_eq!(screen.buffer(), "/X/ \n####");
+    }
+
+    #[test]
+    fn checkerboard_alternates_from_a_set_corner() {
+        let mut screen = Screen::new(5, 4);
+        screen.fill_checkerboard(1);
+        for (y, row) in screen.to_grid().iter().enumerate() {
+            for (x, &pixel) in row.iter().enumerate() {
+                assert_eq!(pixel, (x + y) % 2 == 0, "({}, {})", x, y);
+            }
+        }
+        assert!(screen.get(0, 0));
+
+        screen.fill_checkerboard(2);
+        assert_eq!(screen.to_grid()[1], [true, true, false, false, true]);
+        assert_eq!(screen.to_grid()[2], [false, false, true, true, false]);
+        screen.fill_checkerboard(0);
+        assert!(screen.get(0, 0) && !screen.get(1, 0));
+    }
+
+    #[test]
+    fn invert_flips_every_pixel() {
+        let mut screen = Screen::new(6, 4);
+        screen.set(2, 3, true);
+        screen.invert();
+        assert_eq!(screen.to_grid().iter().flatten().filter(|&&pixel| pixel).count(), 6 * 4 - 1);
+        assert!(!screen.get(2, 3));
+        assert_eq!(screen.intensity(0, 0), 0.0);
+
+        screen.set_intensity(0, 0, 0.25);
+        screen.set_intensity(1, 0, 3.0);
+        screen.invert();
+        assert_eq!((screen.intensity(0, 0), screen.intensity(1, 0), screen.intensity(2, 0)), (0.75, 0.0, 1.0));
+    }
+
+    #[test]
+    fn walk_line_visits_exactly_the_drawn_pixels() {
+        for (start, end) in [((1, 1), (13, 6)), ((12, 0), (3, 7)), ((5, 7), (5, 0)), ((4, 4), (4, 4))] {
+            let mut visited = Vec::new();
+            walk_line(start, end, |x, y| visited.push((x, y)));
+            assert_eq!((visited[0], visited[visited.len() - 1]), (start, end));
+
+            let mut screen = Screen::new(16, 8);
+            screen.draw_line(start, end);
+            let drawn: Vec<_> = (0..8)
+                .flat_map(|y| (0..16).map(move |x| (x, y)))
+                .filter(|&(x, y)| screen.get(x, y))
+                .collect();
+            visited.sort_by_key(|&(x, y)| (y, x));
+            assert_eq!(visited, drawn);
+        }
+    }
+
+    #[test]
+    fn projection_limit_rejects_huge_coordinates() {
+        let screen = Screen::new(80, 40);
+        // Just past the near plane, x = 1 lands 100 screen widths out.
+        let near = Vector3::new(1.0, 0.0, 0.01);
+        let (x, _) = screen.view_to_screen(near, 0.0, 1.0).unwrap();
+        assert!(x > screen.projection_limit * 80.0);
+        assert_eq!(screen.project_view_point(near, 0.0, 1.0), None);
+        assert_eq!(screen.pixel_at(f64::INFINITY, 0.0), None);
+        assert_eq!(screen.pixel_at(f64::NAN, 0.0), None);
+        assert_eq!(screen.pixel_at(79.0, 39.0), Some((79, 39)));
+    }
+
+    #[test]
+    fn border_frames_the_output_with_its_corner_characters() {
+        let mut screen = Screen::new(4, 3);
+        screen.trailing_newline = false;
+        screen.border = Some(BorderChars::BOX_DRAWING);
+        screen.fill_checkerboard(1);
+        screen.build();
+        assert_eq!(screen.buffer(), "┌──┐\n│. │\n└──┘");
+
+        screen.border = Some(BorderChars::ASCII);
+        screen.build();
+        let lines: Vec<_> = screen.buffer().lines().collect();
+        assert!(lines[0].starts_with('+') && lines[0].ends_with('+'));
+        assert!(lines[2].starts_with('+') && lines[2].ends_with('+'));
+        // The border only covers the output, not the pixels under it.
+        assert!(screen.get(0, 0));
+    }
+
+    #[test]
+    fn region_build_returns_just_the_requested_block() {
+        let mut screen = Screen::new(6, 5);
+        screen.pixel_char = '#';
+        screen.background_char = '.';
+        screen.set(3, 2, true);
+        assert_eq!(screen.region_build(2, 1, 2, 2), "..\n.#");
+        assert_eq!(screen.region_build(3, 2, 2, 2), "#.\n..");
+        assert_eq!(screen.region_build(5, 4, 3, 3), ".");
+    }
+
+    #[test]
+    fn pixel_at_rounds_half_pixels_to_even_instead_of_truncating() {
+        let screen = Screen::new(10, 10);
+        assert_eq!(screen.pixel_at(4.4999, 2.0), Some((4, 2)));
+        assert_eq!(screen.pixel_at(4.5, 2.0), Some((4, 2)));
+        assert_eq!(screen.pixel_at(4.5001, 2.0), Some((5, 2)));
+        assert_eq!(screen.pixel_at(5.5, 2.0), Some((6, 2)));
+        assert_eq!(screen.pixel_at(2.0, 3.5), Some((2, 4)));
+    }
+
+    #[test]
+    fn fill_quad_covers_an_aligned_square_exactly() {
+        let mut screen = Screen::new(8, 8);
+        screen.fill_quad((1, 1), (5, 1), (5, 5), (1, 5), true);
+        assert_eq!(screen.pixels.iter().filter(|&&pixel| pixel).count(), 25);
+        for (x, y) in [(1, 1), (5, 1), (5, 5), (1, 5), (3, 3)] {
+            assert!(screen.get(x, y), "({}, {})", x, y);
+        }
+        assert!(!screen.get(0, 3) && !screen.get(6, 3) && !screen.get(3, 0) && !screen.get(3, 6));
+    }
+
+    #[test]
+    fn concave_quad_leaves_the_notch_empty() {
+        let mut screen = Screen::new(8, 8);
+        // An arrowhead pointing right, its reflex corner at (3, 3).
+        screen.fill_quad((0, 0), (6, 3), (0, 6), (3, 3), true);
+        assert!(screen.get(4, 3) && screen.get(6, 3) && screen.get(0, 0) && screen.get(0, 6));
+        assert!(!screen.get(1, 3) && !screen.get(2, 3));
+    }
+
+    #[test]
+    fn diff_ratio_counts_the_changed_fraction() {
+        let (mut before, mut after) = (Screen::new(10, 10), Screen::new(10, 10));
+        before.set(2, 2, true);
+        after.set(2, 2, true);
+        assert_eq!(before.diff_ratio(&after).unwrap(), 0.0);
+
+        after.set(7, 4, true);
+        assert!((before.diff_ratio(&after).unwrap() - 0.01).abs() < 1e-12);
+        after.draw_text(0, 0, "ab");
+        assert!((before.diff_ratio(&after).unwrap() - 0.03).abs() < 1e-12);
+
+        assert!(matches!(
+            before.diff_ratio(&Screen::new(10, 9)),
+            Err(ScreenError::DimensionMismatch { expected: (10, 10), found: (10, 9) })
+        ));
+    }
+
+    #[test]
+    fn build_matches_the_reference_byte_for_byte() {
+        // Speed is measured by the `build` bench; this only pins the output.
+        let configure: [fn(&mut Screen); 9] = [
+            |_| {},
+            |screen| screen.color_support = ColorSupport::Monochrome,
+            |screen| screen.color_support = ColorSupport::Ansi256,
+            |screen| screen.border = Some(BorderChars::BOX_DRAWING),
+            |screen| (screen.flip_horizontal, screen.flip_vertical) = (true, true),
+            |screen| screen.draw_text(1, 1, "label"),
+            |screen| screen.trailing_newline = false,
+            |screen| {
+                screen.set_intensity(3, 2, 0.4);
+                screen.set_intensity(4, 2, 3.0);
+            },
+            |screen| screen.clear(),
+        ];
+        for (case, configure) in configure.iter().enumerate() {
+            let mut screen = Screen::new(12, 5);
+            screen.set(0, 0, true);
+            screen.set_color(2, 1, Color::new(255, 0, 0));
+            screen.set_color(3, 1, Color::new(255, 0, 0));
+            screen.set_color(4, 1, Color::new(0, 0, 255));
+            screen.set_color(11, 4, Color::new(0, 255, 0));
+            screen.add_intensity(6, 3, 0.5);
+            configure(&mut screen);
+            screen.build();
+            assert_eq!(screen.buffer(), reference_build(&screen), "case {}", case);
+        }
+    }
+
+    #[test]
+    fn ascii_only_swaps_braille_and_box_drawing_for_ascii() {
+        let mut screen = Screen::new(6, 3);
+        screen.trailing_newline = false;
+        screen.pixel_char = '⣿';
+        screen.border = Some(BorderChars::BOX_DRAWING);
+        screen.set(1, 1, true);
+        screen.draw_text(2, 1, "⠁é");
+        screen.build();
+        assert_eq!(screen.buffer(), "┌────┐\n│⣿⠁é │\n└────┘");
+
+        screen.encoding = Encoding::AsciiOnly;
+        screen.build();
+        assert_eq!(screen.buffer(), "+----+\n|#.# |\n+----+");
+        assert!(screen.buffer().is_ascii());
+    }
+
+    #[test]
+    fn taken_buffers_are_refilled_and_reused() {
+        let mut screen = Screen::new(3, 2);
+        screen.set(1, 0, true);
+        screen.build();
+        let frame = screen.take_buffer();
+        assert_eq!(frame, " . \n   \n");
+        assert_eq!(screen.buffer(), "");
+        screen.build();
+        assert_eq!(screen.buffer(), frame);
+
+        let mut spare = String::with_capacity(1024);
+        spare.push_str("stale");
+        let allocation = spare.as_ptr();
+        screen.set_buffer(spare);
+        screen.build();
+        assert_eq!(screen.buffer(), frame);
+        let reused = screen.take_buffer();
+        assert_eq!(reused.as_ptr(), allocation);
+    }
+
+    #[test]
+    fn view_cull_follows_perspective_strength() {
+        let mut screen = Screen::new(80, 40);
+        let cube = CUBE_VERTICES.map(|vertex| vertex * 0.1 + Vector3::new(1.0, 0.0, 0.5));
+        screen.perspective_strength = 0.0;
+        assert!(!screen.is_outside_view(&cube, 5.0, 1.0));
+        screen.perspective_strength = 1.0;
+        assert!(screen.is_outside_view(&cube, 5.0, 1.0));
+    }
+
+    #[test]
+    fn view_cull_follows_the_principal_point() {
+        let mut screen = Screen::new(80, 40);
+        // Centered, the cube is beyond the right edge; with the principal
+        // point moved left it lands at column 60.
+        let cube = CUBE_VERTICES.map(|vertex| vertex + Vector3::new(13.0, 0.0, 10.0));
+        assert!(screen.is_outside_view(&cube, 5.0, 1.0));
+        screen.principal_point = (0.1, 0.5);
+        assert!(!screen.is_outside_view(&cube, 5.0, 1.0));
+        assert_eq!(screen.project_view_point(Vector3::new(13.0, 0.0, 10.0), 5.0, 1.0), Some((60, 20)));
+    }
+
+    #[test]
+    fn char_aspect_keeps_units_square() {
+        let point = Vector3::new(1.0, 0.5, 5.0);
+        let mut screen = Screen::new(160, 40);
+        screen.char_aspect = Some(2.0);
+        // A unit spans twice as many columns as rows, whatever the width.
+        assert_eq!(screen.view_to_screen(point, 5.0, 5.0), Some((120.0, 10.0)));
+
+        let mut square = Screen::new(160, 80);
+        let unset = square.view_to_screen(point, 5.0, 5.0);
+        square.char_aspect = Some(2.0);
+        assert_eq!(square.view_to_screen(point, 5.0, 5.0), unset);
+    }
+
+    #[test]
+    fn try_project_agrees_with_project_at_the_right_edge() {
+        let screen = Screen::new(10, 10);
+        let camera = Camera::default();
+        // At depth 5 one unit spans one pixel, so these land at x = 9.75 and 9.25.
+        let past_edge = Vector3::new(4.75, 2.0, 0.0);
+        assert_eq!(camera.project(&screen, past_edge), None);
+        assert_eq!(screen.try_project(past_edge, &camera), Err(ProjectError::OutOfBounds));
+
+        let inside = Vector3::new(4.25, 2.0, 0.0);
+        assert_eq!(camera.project(&screen, inside), Some((9, 5)));
+        assert_eq!(screen.try_project(inside, &camera), Ok((9.25, 5.0, 5.0)));
+    }
+}
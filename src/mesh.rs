@@ -0,0 +1,301 @@
+use nalgebra::Vector3;
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::PI,
+};
+
+use crate::rng::SplitMix64;
+
+/// A wireframe mesh: its vertices and the index pairs they are joined by.
+pub type Mesh = (Vec<Vector3<f64>>, Vec<(usize, usize)>);
+
+/// A mesh that also lists its quads, each counter-clockwise seen from outside.
+pub type QuadMesh = (Vec<Vector3<f64>>, Vec<(usize, usize)>, Vec<[usize; 4]>);
+
+pub const CUBE_VERTICES: [Vector3<f64>; 8] = [
+    Vector3::new(-1.0, -1.0, -1.0),
+    Vector3::new(1.0, -1.0, -1.0),
+    Vector3::new(1.0, 1.0, -1.0),
+    Vector3::new(-1.0, 1.0, -1.0),
+    Vector3::new(-1.0, -1.0, 1.0),
+    Vector3::new(1.0, -1.0, 1.0),
+    Vector3::new(1.0, 1.0, 1.0),
+    Vector3::new(-1.0, 1.0, 1.0),
+];
+
+pub const CUBE_EDGES: [(usize, usize); 12] = [
+    (0, 1), (1, 2), (2, 3), (3, 0),
+    (4, 5), (5, 6), (6, 7), (7, 4),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+/// The cube's faces as vertex loops, counter-clockwise seen from outside.
+pub const CUBE_FACES: [[usize; 4]; 6] = [
+    [0, 3, 2, 1], [4, 5, 6, 7],
+    [0, 1, 5, 4], [3, 7, 6, 2],
+    [0, 4, 7, 3], [1, 2, 6, 5],
+];
+
+/// Splits every edge into `segments` pieces by inserting evenly spaced points
+/// in object space, so that curved shapes stay smooth after projection.
+///
+/// The original vertices keep their indices; the new points are appended
+/// after them. A `segments` value of 0 or 1 returns the mesh unchanged.
+pub fn subdivide_edges(
+    vertices: &[Vector3<f64>],
+    edges: &[(usize, usize)],
+    segments: usize,
+) -> (Vec<Vector3<f64>>, Vec<(usize, usize)>) {
+    let mut new_vertices = vertices.to_vec();
+    if segments <= 1 {
+        return (new_vertices, edges.to_vec());
+    }
+
+    let mut new_edges = Vec::with_capacity(edges.len() * segments);
+    for &(start, end) in edges {
+        let (a, b) = (vertices[start], vertices[end]);
+        let mut previous = start;
+        for i in 1..segments {
+            new_vertices.push(a.lerp(&b, i as f64 / segments as f64));
+            let current = new_vertices.len() - 1;
+            new_edges.push((previous, current));
+            previous = current;
+        }
+        new_edges.push((previous, end));
+    }
+    (new_vertices, new_edges)
+}
+
+/// Builds the cube of `CUBE_VERTICES` with every face cut into a
+/// `subdivisions` by `subdivisions` grid of quads. Neighbouring faces share
+/// the vertices and edges along the cube's edges, so the surface has
+/// `6 * n * n + 2` vertices and `12 * n * n` edges. A `subdivisions` of 0
+/// is treated as 1.
+pub fn subdivided_cube(subdivisions: usize) -> QuadMesh {
+    let n = subdivisions.max(1);
+    let mut vertices = Vec::with_capacity(6 * n * n + 2);
+    let mut edges = Vec::with_capacity(12 * n * n);
+    let mut faces = Vec::with_capacity(6 * n * n);
+
+    // Points are found by their coordinates on an (n + 1)^3 lattice, so the
+    // faces meeting along an edge pick up the same vertices.
+    let mut lattice: HashMap<[usize; 3], usize> = HashMap::new();
+    let mut seen_edges = HashSet::new();
+    let corner = |index: usize| CUBE_VERTICES[index].map(|coordinate| if coordinate > 0.0 { n } else { 0 });
+    for face in CUBE_FACES {
+        let [origin, along_u, _, along_v] = face.map(corner);
+        // Each coordinate steps by -1, 0 or +1 per grid cell along u and v.
+        let point = |u: usize, v: usize| -> [usize; 3] {
+            std::array::from_fn(|axis| {
+                let step = |to: usize| (to as isize - origin[axis] as isize) / n as isize;
+                (origin[axis] as isize + step(along_u[axis]) * u as isize + step(along_v[axis]) * v as isize) as usize
+            })
+        };
+        for v in 0..n {
+            for u in 0..n {
+                let quad = [(u, v), (u + 1, v), (u + 1, v + 1), (u, v + 1)].map(|(u, v)| {
+                    *lattice.entry(point(u, v)).or_insert_with_key(|coordinates| {
+                        vertices.push(coordinates.map(|c| c as f64 * 2.0 / n as f64 - 1.0).into());
+                        vertices.len() - 1
+                    })
+                });
+                for i in 0..4 {
+                    let (start, end) = (quad[i], quad[(i + 1) % 4]);
+                    if seen_edges.insert((start.min(end), start.max(end))) {
+                        edges.push((start, end));
+                    }
+                }
+                faces.push(quad);
+            }
+        }
+    }
+    (vertices, edges, faces)
+}
+
+/// Builds a unit sphere wireframe from `latitudes` rings between the poles
+/// and `longitudes` meridians.
+///
+/// Vertex 0 is the north pole and the last vertex the south pole; the rings
+/// are stored in between, north to south.
+pub fn uv_sphere(latitudes: usize, longitudes: usize) -> (Vec<Vector3<f64>>, Vec<(usize, usize)>) {
+    let mut vertices = Vec::with_capacity(latitudes * longitudes + 2);
+    let mut edges = Vec::with_capacity(latitudes * longitudes * 2 + longitudes);
+
+    vertices.push(Vector3::new(0.0, 1.0, 0.0));
+    for ring in 0..latitudes {
+        let polar = PI * (ring + 1) as f64 / (latitudes + 1) as f64;
+        for meridian in 0..longitudes {
+            let azimuth = 2.0 * PI * meridian as f64 / longitudes as f64;
+            vertices.push(Vector3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                polar.sin() * azimuth.sin(),
+            ));
+        }
+    }
+    vertices.push(Vector3::new(0.0, -1.0, 0.0));
+    let south_pole = vertices.len() - 1;
+
+    let ring_vertex = |ring: usize, meridian: usize| 1 + ring * longitudes + meridian % longitudes;
+    for ring in 0..latitudes {
+        for meridian in 0..longitudes {
+            edges.push((ring_vertex(ring, meridian), ring_vertex(ring, meridian + 1)));
+        }
+    }
+    for meridian in 0..longitudes {
+        let mut previous = 0;
+        for ring in 0..latitudes {
+            edges.push((previous, ring_vertex(ring, meridian)));
+            previous = ring_vertex(ring, meridian);
+        }
+        edges.push((previous, south_pole));
+    }
+    (vertices, edges)
+}
+
+/// Builds a deterministic sphere with about `target_edges` edges, for
+/// benchmarks and scaling tests. The count is within `sqrt(target_edges)` of
+/// the target, with a minimum of three latitudes and three longitudes.
+pub fn stress_mesh(target_edges: usize) -> Mesh {
+    // A sphere has `longitudes * (2 * latitudes + 1)` edges; twice as many
+    // longitudes as latitudes keeps its cells roughly square.
+    let latitudes = ((target_edges as f64 / 4.0).sqrt().round() as usize).max(3);
+    let longitudes = ((target_edges as f64 / (2 * latitudes + 1) as f64).round() as usize).max(3);
+    uv_sphere(latitudes, longitudes)
+}
+
+/// Generates `n` points uniformly distributed over the unit sphere. The same
+/// `seed` always yields the same points.
+pub fn random_sphere_points(n: usize, seed: u64) -> Vec<Vector3<f64>> {
+    let mut rng = SplitMix64::new(seed);
+    (0..n)
+        .map(|_| {
+            // Uniform height and azimuth give a uniform area distribution,
+            // without the clustering at the poles of uniform latitude.
+            let z = 2.0 * rng.next_f64() - 1.0;
+            let azimuth = 2.0 * PI * rng.next_f64();
+            let radius = (1.0 - z * z).sqrt();
+            Vector3::new(radius * azimuth.cos(), radius * azimuth.sin(), z)
+        })
+        .collect()
+}
+
+/// The average of `vertices`, accumulated relative to the first one so large
+/// coordinates do not swamp small offsets. `None` for an empty slice.
+pub fn face_centroid(vertices: &[Vector3<f64>]) -> Option<Vector3<f64>> {
+    let &origin = vertices.first()?;
+    let offset: Vector3<f64> = vertices.iter().map(|vertex| vertex - origin).sum();
+    Some(origin + offset / vertices.len() as f64)
+}
+
+/// The unit normal of a planar polygon whose vertices are in
+/// counter-clockwise order when viewed from the front.
+///
+/// Uses Newell's method, which stays stable for nearly collinear edges.
+/// Returns `None` for faces with fewer than three vertices or no measurable
+/// area, and for non-finite input.
+pub fn face_normal(vertices: &[Vector3<f64>]) -> Option<Vector3<f64>> {
+    if vertices.len() < 3 {
+        return None;
+    }
+    let centroid = face_centroid(vertices)?;
+
+    let mut normal = Vector3::zeros();
+    let mut extent: f64 = 0.0;
+    for (i, vertex) in vertices.iter().enumerate() {
+        let current = vertex - centroid;
+        let next = vertices[(i + 1) % vertices.len()] - centroid;
+        normal += current.cross(&next);
+        extent = extent.max(current.norm());
+    }
+
+    // Treat areas that are negligible next to the face's size as degenerate.
+    let length = normal.norm();
+    if !length.is_finite() || length <= 1e-12 * extent * extent {
+        return None;
+    }
+    Some(normal / length)
+}
+
+/// Interpolates linearly between two poses of the same mesh: `t` of 0.0
+/// gives `a` and 1.0 gives `b`. `None` if they have different vertex counts.
+pub fn morph(a: &[Vector3<f64>], b: &[Vector3<f64>], t: f64) -> Option<Vec<Vector3<f64>>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(a, b)| a.lerp(b, t)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subdividing_an_edge_by_four_adds_three_points() {
+        let vertices = [Vector3::zeros(), Vector3::new(4.0, 0.0, 0.0)];
+        let (new_vertices, new_edges) = subdivide_edges(&vertices, &[(0, 1)], 4);
+        assert_eq!(new_vertices.len(), 2 + 3);
+        assert_eq!(new_edges, [(0, 2), (2, 3), (3, 4), (4, 1)]);
+        for (i, point) in new_vertices[2..].iter().enumerate() {
+            assert_eq!(*point, Vector3::new(i as f64 + 1.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn random_sphere_points_are_reproducible_unit_vectors() {
+        let points = random_sphere_points(200, 42);
+        assert_eq!(points.len(), 200);
+        assert_eq!(points, random_sphere_points(200, 42));
+        assert_ne!(points, random_sphere_points(200, 43));
+        assert!(points.iter().all(|point| (point.norm() - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn collinear_points_have_no_normal() {
+        let collinear = [Vector3::zeros(), Vector3::new(1.0, 1.0, 1.0), Vector3::new(2.0, 2.0, 2.0)];
+        assert_eq!(face_normal(&collinear), None);
+        assert_eq!(face_normal(&collinear[..2]), None);
+        assert_eq!(face_centroid(&[]), None);
+    }
+
+    #[test]
+    fn normal_and_centroid_survive_large_coordinates() {
+        let offset = Vector3::repeat(1e9);
+        let face = CUBE_FACES[1].map(|index| CUBE_VERTICES[index] + offset);
+        assert_eq!(face_centroid(&face), Some(Vector3::new(1e9, 1e9, 1e9 + 1.0)));
+        let normal = face_normal(&face).unwrap();
+        assert!((normal - Vector3::z()).norm() < 1e-9, "{:?}", normal);
+    }
+
+    #[test]
+    fn morph_interpolates_between_the_poses() {
+        let a = CUBE_VERTICES.to_vec();
+        let b: Vec<_> = a.iter().map(|vertex| vertex * 3.0).collect();
+        assert_eq!(morph(&a, &b, 0.0), Some(a.clone()));
+        assert_eq!(morph(&a, &b, 1.0), Some(b.clone()));
+        let middle: Vec<_> = a.iter().map(|vertex| vertex * 2.0).collect();
+        assert_eq!(morph(&a, &b, 0.5), Some(middle));
+        assert_eq!(morph(&a, &b[1..], 0.5), None);
+    }
+
+    #[test]
+    fn stress_mesh_is_close_to_the_target_edge_count() {
+        for target in [100, 1_000, 10_000, 100_000] {
+            let (vertices, edges) = stress_mesh(target);
+            let tolerance = (target as f64).sqrt();
+            assert!((edges.len() as f64 - target as f64).abs() <= tolerance, "{} for {}", edges.len(), target);
+            assert!(edges.iter().all(|&(start, end)| start < vertices.len() && end < vertices.len()));
+        }
+        assert_eq!(stress_mesh(5_000), stress_mesh(5_000));
+    }
+
+    #[test]
+    fn subdivided_cube_shares_vertices_and_edges_between_faces() {
+        let (vertices, edges, faces) = subdivided_cube(2);
+        assert_eq!((vertices.len(), edges.len(), faces.len()), (26, 48, 24));
+        // Every vertex lies on the surface of the cube of `CUBE_VERTICES`.
+        assert!(vertices.iter().all(|vertex| (vertex.amax() - 1.0).abs() < 1e-12));
+        let (_, edges, faces) = subdivided_cube(0);
+        assert_eq!((edges.len(), faces.len()), (12, 6));
+    }
+}
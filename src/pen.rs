@@ -0,0 +1,77 @@
+use crate::Screen;
+
+/// Turtle-style cursor for drawing 2D overlays onto a `Screen`.
+pub struct Pen<'a> {
+    screen: &'a mut Screen,
+    position: (usize, usize),
+    down: bool,
+}
+
+impl<'a> Pen<'a> {
+    /// Creates a lowered pen at the top-left corner.
+    pub fn new(screen: &'a mut Screen) -> Self {
+        Self {
+            screen,
+            position: (0, 0),
+            down: true,
+        }
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Moves to `(x, y)` without drawing.
+    pub fn move_to(&mut self, x: usize, y: usize) {
+        self.position = (x, y);
+    }
+
+    /// Moves to `(x, y)`, drawing a line from the current position if the pen
+    /// is down.
+    pub fn line_to(&mut self, x: usize, y: usize) {
+        if self.down {
+            self.screen.draw_line(self.position, (x, y));
+        }
+        self.position = (x, y);
+    }
+
+    pub fn pen_up(&mut self) {
+        self.down = false;
+    }
+
+    pub fn pen_down(&mut self) {
+        self.down = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_a_rectangle_through_its_corners() {
+        let mut screen = Screen::new(10, 6);
+        let mut pen = Pen::new(&mut screen);
+        pen.move_to(1, 1);
+        for (x, y) in [(8, 1), (8, 4), (1, 4), (1, 1)] {
+            pen.line_to(x, y);
+        }
+        assert_eq!(pen.position(), (1, 1));
+        for (x, y) in [(1, 1), (8, 1), (8, 4), (1, 4)] {
+            assert!(screen.get(x, y), "corner ({}, {}) not set", x, y);
+        }
+        assert!(!screen.get(4, 2));
+    }
+
+    #[test]
+    fn lifted_pen_moves_without_drawing() {
+        let mut screen = Screen::new(10, 6);
+        let mut pen = Pen::new(&mut screen);
+        pen.pen_up();
+        pen.line_to(5, 5);
+        pen.pen_down();
+        pen.line_to(5, 5);
+        assert!(screen.get(5, 5));
+        assert!(!screen.get(0, 0));
+    }
+}
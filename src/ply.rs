@@ -0,0 +1,273 @@
+use nalgebra::Vector3;
+use std::{collections::HashSet, error::Error, fmt, fs, io, path::Path};
+
+use crate::Color;
+
+/// A mesh with one color per vertex, as `load_ply` returns it, ready for
+/// `Screen::draw_mesh_colored`.
+pub type ColoredMesh = (Vec<Vector3<f64>>, Vec<(usize, usize)>, Vec<Color>);
+
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// The file is not valid ASCII PLY; `line` counts from 1.
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::Io(error) => write!(f, "failed to read ply file: {}", error),
+            PlyError::Parse { line, message } => write!(f, "invalid ply on line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for PlyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlyError::Io(error) => Some(error),
+            PlyError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(error: io::Error) -> Self {
+        PlyError::Io(error)
+    }
+}
+
+const WHITE: Color = Color::new(255, 255, 255);
+
+struct Property {
+    name: String,
+    is_list: bool,
+    is_float: bool,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Reads an ASCII PLY file. See `parse_ply`.
+pub fn load_ply(path: impl AsRef<Path>) -> Result<ColoredMesh, PlyError> {
+    parse_ply(&fs::read_to_string(path)?)
+}
+
+/// Parses ASCII PLY into vertices, edges and per-vertex colors.
+///
+/// Vertices need `x`, `y` and `z`; vertices without `red`, `green` and
+/// `blue` are white. Float color channels are taken to range over 0 to 1.
+/// Edges come from `edge` elements and from the sides of every `face`
+/// polygon, each shared side listed once; their vertex indices must be
+/// non-negative integers. Other elements are skipped.
+pub fn parse_ply(source: &str) -> Result<ColoredMesh, PlyError> {
+    let mut lines = source.lines().enumerate().map(|(index, line)| (index + 1, line.trim()));
+    let error = |line: usize, message: &str| PlyError::Parse { line, message: message.to_string() };
+
+    match lines.next() {
+        Some((_, "ply")) => {}
+        _ => return Err(error(1, "missing ply magic number")),
+    }
+
+    let mut elements: Vec<Element> = Vec::new();
+    let mut last_line = 1;
+    loop {
+        let Some((number, line)) = lines.next() else {
+            return Err(error(last_line, "missing end_header"));
+        };
+        last_line = number;
+        let words: Vec<_> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", "ascii", _] => {}
+            ["format", ..] => return Err(error(number, "only the ascii format is supported")),
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["element", name, count] => {
+                let count = count.parse().map_err(|_| error(number, "invalid element count"))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            }
+            ["property", "list", _, kind, name] | ["property", kind, name] => {
+                let element = elements.last_mut().ok_or_else(|| error(number, "property before any element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    is_list: words[1] == "list",
+                    is_float: matches!(*kind, "float" | "double" | "float32" | "float64"),
+                });
+            }
+            _ => return Err(error(number, "unrecognized header line")),
+        }
+    }
+
+    let mut vertices = Vec::new();
+    let mut colors = Vec::new();
+    let mut polygons: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut edges = Vec::new();
+    for element in &elements {
+        for _ in 0..element.count {
+            let Some((number, line)) = lines.next() else {
+                return Err(error(last_line, "unexpected end of file"));
+            };
+            last_line = number;
+            let mut tokens = line.split_whitespace();
+            let mut next_token = || tokens.next().ok_or_else(|| error(number, "too few values"));
+            // Counts and vertex indices must be non-negative integers.
+            let index = |token: &str| token.parse::<usize>().map_err(|_| error(number, "invalid index"));
+
+            let mut position = [None; 3];
+            let mut color = [None; 3];
+            let mut edge = [None; 2];
+            for property in &element.properties {
+                if property.is_list {
+                    let length = index(next_token()?)?;
+                    if matches!(property.name.as_str(), "vertex_indices" | "vertex_index") {
+                        let indices = (0..length).map(|_| index(next_token()?)).collect::<Result<_, _>>()?;
+                        polygons.push((number, indices));
+                    } else {
+                        for _ in 0..length {
+                            next_token()?;
+                        }
+                    }
+                    continue;
+                }
+                let token = next_token()?;
+                let edge_end = match (element.name.as_str(), property.name.as_str()) {
+                    ("edge", "vertex1") => Some(0),
+                    ("edge", "vertex2") => Some(1),
+                    _ => None,
+                };
+                if let Some(end) = edge_end {
+                    edge[end] = Some(index(token)?);
+                    continue;
+                }
+                let value: f64 = token.parse().map_err(|_| error(number, "invalid number"))?;
+                let scaled = if property.is_float { value * 255.0 } else { value };
+                let channel = || scaled.round().clamp(0.0, 255.0) as u8;
+                match (element.name.as_str(), property.name.as_str()) {
+                    ("vertex", "x") => position[0] = Some(value),
+                    ("vertex", "y") => position[1] = Some(value),
+                    ("vertex", "z") => position[2] = Some(value),
+                    ("vertex", "red") => color[0] = Some(channel()),
+                    ("vertex", "green") => color[1] = Some(channel()),
+                    ("vertex", "blue") => color[2] = Some(channel()),
+                    _ => {}
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let [Some(x), Some(y), Some(z)] = position else {
+                        return Err(error(number, "vertex without x, y and z"));
+                    };
+                    vertices.push(Vector3::new(x, y, z));
+                    colors.push(match color {
+                        [Some(r), Some(g), Some(b)] => Color::new(r, g, b),
+                        _ => WHITE,
+                    });
+                }
+                "edge" => {
+                    let [Some(start), Some(end)] = edge else {
+                        return Err(error(number, "edge without vertex1 and vertex2"));
+                    };
+                    edges.push((number, start, end));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut unique_edges = Vec::new();
+    let sides = polygons.iter().flat_map(|(number, polygon)| {
+        (0..polygon.len()).map(move |i| (*number, polygon[i], polygon[(i + 1) % polygon.len()]))
+    });
+    for (number, start, end) in edges.into_iter().chain(sides) {
+        if start >= vertices.len() || end >= vertices.len() {
+            return Err(error(number, "vertex index out of range"));
+        }
+        if start != end && seen.insert((start.min(end), start.max(end))) {
+            unique_edges.push((start, end));
+        }
+    }
+
+    Ok((vertices, unique_edges, colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Camera, Screen, Transform};
+
+    const TRIANGLE: &str = "ply
+format ascii 1.0
+comment a colored triangle
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+0 1 0 0 0 255
+3 0 1 2
+";
+
+    #[test]
+    fn parses_colored_vertices_and_face_edges() {
+        let (vertices, edges, colors) = parse_ply(TRIANGLE).unwrap();
+        assert_eq!(vertices, vec![Vector3::zeros(), Vector3::x(), Vector3::y()]);
+        assert_eq!(edges, vec![(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(colors, vec![Color::new(255, 0, 0), Color::new(0, 255, 0), Color::new(0, 0, 255)]);
+    }
+
+    #[test]
+    fn vertices_without_colors_are_white() {
+        let source = TRIANGLE
+            .replace("property uchar red\nproperty uchar green\nproperty uchar blue\n", "")
+            .replace(" 255 0 0\n", "\n")
+            .replace(" 0 255 0\n", "\n")
+            .replace(" 0 0 255\n", "\n");
+        let (vertices, _, colors) = parse_ply(&source).unwrap();
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(colors, vec![WHITE; 3]);
+    }
+
+    #[test]
+    fn parsed_meshes_draw_in_their_vertex_colors() {
+        let (vertices, edges, colors) = parse_ply(TRIANGLE).unwrap();
+        let mut screen = Screen::new(40, 20);
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let stats = screen.draw_mesh_colored(&vertices, &edges, &colors, &Transform::default(), &camera);
+        assert_eq!(stats.edges_drawn, 3);
+        for (&vertex, &color) in vertices.iter().zip(&colors) {
+            let (x, y) = camera.project(&screen, vertex).unwrap();
+            assert_eq!(screen.color(x, y), Some(color));
+        }
+    }
+
+    #[test]
+    fn skips_float_lists_of_other_properties() {
+        let faces = "property list uchar int vertex_indices";
+        let source = TRIANGLE
+            .replace(faces, &format!("property list uchar float uv\n{}", faces))
+            .replace("3 0 1 2", "2 0.25 0.75 3 0 1 2");
+        let (_, edges, _) = parse_ply(&source).unwrap();
+        assert_eq!(edges.len(), 3);
+    }
+
+    #[test]
+    fn rejects_negative_and_fractional_indices() {
+        for face in ["3 0 1 -1", "3 0 1 0.7"] {
+            let source = TRIANGLE.replace("3 0 1 2", face);
+            assert!(matches!(parse_ply(&source), Err(PlyError::Parse { line: 17, .. })), "{}", face);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+/// Rendering quality levels, lowest first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    /// A single plain pass.
+    Low,
+    Medium,
+    /// Everything enabled.
+    #[default]
+    High,
+}
+
+impl Quality {
+    /// Sub-frames to pass to `render_frame_supersampled` at this level.
+    pub fn samples(self) -> usize {
+        match self {
+            Quality::Low => 1,
+            Quality::Medium => 2,
+            Quality::High => 4,
+        }
+    }
+
+    fn lower(self) -> Self {
+        match self {
+            Quality::High => Quality::Medium,
+            _ => Quality::Low,
+        }
+    }
+
+    fn higher(self) -> Self {
+        match self {
+            Quality::Low => Quality::Medium,
+            _ => Quality::High,
+        }
+    }
+}
+
+/// Picks a quality level from recent frame times so the frame rate holds.
+///
+/// Quality drops one level after `patience` consecutive frames over the
+/// budget and rises one level after as many frames under half of it, so a
+/// single slow or fast frame does not make it flicker between levels.
+pub struct AdaptiveQuality {
+    pub budget: Duration,
+    pub patience: usize,
+    quality: Quality,
+    over_budget: usize,
+    under_budget: usize,
+}
+
+impl AdaptiveQuality {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            patience: 3,
+            quality: Quality::High,
+            over_budget: 0,
+            under_budget: 0,
+        }
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Records how long the last frame took and returns the quality to
+    /// render the next one at.
+    pub fn update(&mut self, frame_time: Duration) -> Quality {
+        if frame_time > self.budget {
+            self.over_budget += 1;
+            self.under_budget = 0;
+        } else if frame_time < self.budget / 2 {
+            self.under_budget += 1;
+            self.over_budget = 0;
+        } else {
+            self.over_budget = 0;
+            self.under_budget = 0;
+        }
+
+        if self.over_budget >= self.patience {
+            self.quality = self.quality.lower();
+            self.over_budget = 0;
+        } else if self.under_budget >= self.patience {
+            self.quality = self.quality.higher();
+            self.under_budget = 0;
+        }
+        self.quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_times_move_the_quality_one_level_at_a_time() {
+        let mut quality = AdaptiveQuality::new(Duration::from_millis(16));
+        let (slow, fast) = (Duration::from_millis(30), Duration::from_millis(4));
+        assert_eq!(quality.quality(), Quality::High);
+
+        // A single slow frame is not enough to drop a level.
+        assert_eq!(quality.update(slow), Quality::High);
+        assert_eq!(quality.update(fast), Quality::High);
+        let levels: Vec<_> = (0..6).map(|_| quality.update(slow)).collect();
+        assert_eq!(levels[2], Quality::Medium);
+        assert_eq!(levels[5], Quality::Low);
+        assert_eq!(quality.update(slow), Quality::Low);
+
+        // Frames within budget but over half of it hold the level.
+        for _ in 0..5 {
+            assert_eq!(quality.update(Duration::from_millis(12)), Quality::Low);
+        }
+        let levels: Vec<_> = (0..6).map(|_| quality.update(fast)).collect();
+        assert_eq!(levels[2], Quality::Medium);
+        assert_eq!(levels[5], Quality::High);
+        assert_eq!(quality.quality().samples(), 4);
+    }
+}
@@ -0,0 +1,1290 @@
+use nalgebra::Vector3;
+use std::{
+    io::{stdout, BufWriter, StdoutLock, Write},
+    ops::AddAssign,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    face_centroid, face_normal, walk_line, BackfaceStyle, Camera, Color, ProjectError, Screen, ScreenError, Transform,
+};
+
+/// An output backend that frames are presented to once built.
+pub trait Renderer {
+    fn present(&mut self, screen: &Screen);
+}
+
+/// Draws frames in place on the terminal.
+#[cfg(feature = "terminal")]
+pub struct TerminalRenderer;
+
+#[cfg(feature = "terminal")]
+impl Renderer for TerminalRenderer {
+    fn present(&mut self, screen: &Screen) {
+        screen.render();
+    }
+}
+
+/// Presents frames to a writer held for the renderer's lifetime, such as a
+/// locked and buffered stdout. Each frame, cursor-home escape included, is
+/// composed up front and handed over in a single `write_all` and flush, so
+/// the terminal never shows half of one frame over the previous one.
+pub struct WriterRenderer<W: Write> {
+    out: W,
+    frame: String,
+}
+
+impl<W: Write> WriterRenderer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            frame: String::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl WriterRenderer<BufWriter<StdoutLock<'static>>> {
+    /// Locks stdout once, instead of on every frame as `TerminalRenderer` does.
+    pub fn stdout() -> Self {
+        Self::new(BufWriter::new(stdout().lock()))
+    }
+}
+
+impl<W: Write> Renderer for WriterRenderer<W> {
+    fn present(&mut self, screen: &Screen) {
+        self.frame.clear();
+        self.frame.push_str("\x1b[H");
+        self.frame.push_str(screen.buffer());
+        self.out.write_all(self.frame.as_bytes()).unwrap();
+        self.out.flush().unwrap();
+    }
+}
+
+/// How `draw_world_edges` draws an edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EdgeStyle {
+    Solid,
+    Dashed,
+    /// Blended from the first color at the edge's start to the second at its
+    /// end.
+    Colored(Color, Color),
+}
+
+/// Counters collected while rendering a single frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub edges_drawn: usize,
+    pub vertices_projected: usize,
+    pub vertices_culled: usize,
+    pub pixels_set: usize,
+    /// Edges skipped for lying entirely behind the near plane, or for an end
+    /// that still could not be projected after clipping, as happens when the
+    /// near plane is at or behind the camera.
+    pub edges_behind_camera: usize,
+    /// Edges drawn shortened, having crossed the near plane or a screen edge.
+    pub edges_clipped: usize,
+    /// Edges skipped for lying entirely outside the screen.
+    pub edges_offscreen: usize,
+    /// Meshes skipped whole for lying outside the view volume.
+    pub objects_culled: usize,
+}
+
+impl AddAssign for FrameStats {
+    fn add_assign(&mut self, other: Self) {
+        self.edges_drawn += other.edges_drawn;
+        self.vertices_projected += other.vertices_projected;
+        self.vertices_culled += other.vertices_culled;
+        self.pixels_set += other.pixels_set;
+        self.edges_behind_camera += other.edges_behind_camera;
+        self.edges_clipped += other.edges_clipped;
+        self.edges_offscreen += other.edges_offscreen;
+        self.objects_culled += other.objects_culled;
+    }
+}
+
+/// Clears `screen`, draws the mesh rotated by `rotation` and builds the frame,
+/// returning what was drawn.
+pub fn render_frame(
+    screen: &mut Screen,
+    vertices: &[Vector3<f64>],
+    edges: &[(usize, usize)],
+    rotation: Vector3<f64>,
+    camera: &Camera,
+) -> FrameStats {
+    screen.clear_with_persistence();
+    if screen.jitter_enabled {
+        screen.advance_jitter();
+    }
+    let mut stats = screen.draw_mesh(vertices, edges, &Transform::from_rotation(rotation), camera);
+    stats.pixels_set = screen.pixels.iter().filter(|&&pixel| pixel).count();
+    let tone_mapping = screen.tone_mapping;
+    if screen.persistence > 0.0 {
+        // Fresh pixels go in at full intensity so they fade out over the
+        // following frames. As with supersampling, tone mapping would show
+        // them at half brightness, so it is skipped.
+        for (intensity, &pixel) in screen.intensity.iter_mut().zip(&screen.pixels) {
+            if pixel {
+                *intensity = intensity.max(1.0);
+            }
+        }
+        screen.tone_mapping = false;
+    }
+    screen.build();
+    screen.tone_mapping = tone_mapping;
+    stats
+}
+
+/// Renders `samples` sub-frames at rotations spread evenly from `rotation`
+/// towards `rotation + rotation_delta`, averaging their coverage into the
+/// intensity buffer for smooth motion at low frame rates.
+///
+/// Edge and vertex counts are summed over the sub-frames; `pixels_set` counts
+/// the pixels any sub-frame touched.
+pub fn render_frame_supersampled(
+    screen: &mut Screen,
+    vertices: &[Vector3<f64>],
+    edges: &[(usize, usize)],
+    rotation: Vector3<f64>,
+    rotation_delta: Vector3<f64>,
+    camera: &Camera,
+    samples: usize,
+) -> FrameStats {
+    let samples = samples.max(1);
+    let weight = 1.0 / samples as f64;
+    let mut stats = FrameStats::default();
+    screen.clear_with_persistence();
+
+    for sample in 0..samples {
+        screen.pixels.fill(false);
+        if screen.jitter_enabled {
+            screen.advance_jitter();
+        }
+        let sample_rotation = rotation + rotation_delta * (sample as f64 * weight);
+        stats += screen.draw_mesh(vertices, edges, &Transform::from_rotation(sample_rotation), camera);
+
+        for (intensity, &pixel) in screen.intensity.iter_mut().zip(&screen.pixels) {
+            if pixel {
+                *intensity += weight;
+            }
+        }
+    }
+
+    screen.pixels.fill(false);
+    stats.pixels_set = screen.intensity.iter().filter(|&&intensity| intensity > 0.0).count();
+    // Averaged coverage never goes above 1.0, which tone mapping would squeeze
+    // into the lower half of the ramp, so it is clamped instead.
+    let tone_mapping = std::mem::replace(&mut screen.tone_mapping, false);
+    screen.build();
+    screen.tone_mapping = tone_mapping;
+    stats
+}
+
+impl Screen {
+    /// Projects every point in `points` through `camera`, like calling
+    /// `Camera::project` on each but computing the view rotation once.
+    pub fn project_points(&self, points: &[Vector3<f64>], camera: &Camera) -> Vec<Option<(usize, usize)>> {
+        let rotation = camera.view_rotation();
+        let reference_z = (rotation * -camera.position).z;
+        points
+            .iter()
+            .map(|&point| {
+                self.project_view_point(rotation * (point - camera.position), reference_z, camera.display_surface_z)
+            })
+            .collect()
+    }
+
+    /// Like `Camera::project`, but returning the continuous screen position
+    /// and camera-space depth, or why the point cannot be shown. It succeeds
+    /// exactly when `Camera::project` returns a pixel.
+    pub fn try_project(&self, point: Vector3<f64>, camera: &Camera) -> Result<(f64, f64, f64), ProjectError> {
+        let rotation = camera.view_rotation();
+        let reference_z = (rotation * -camera.position).z;
+        let view_point = rotation * (point - camera.position);
+        if !view_point.iter().all(|coordinate| coordinate.is_finite()) {
+            return Err(ProjectError::NonFinite);
+        }
+        let (x, y) = self
+            .view_to_screen(view_point, reference_z, camera.display_surface_z)
+            .ok_or(ProjectError::BehindCamera)?;
+        if !x.is_finite() || !y.is_finite() {
+            return Err(ProjectError::NonFinite);
+        }
+        if self.pixel_at(x, y).is_none() {
+            return Err(ProjectError::OutOfBounds);
+        }
+        Ok((x, y, view_point.z))
+    }
+
+    /// Transforms, projects and plots a point cloud. Points are depth tested
+    /// against the depth buffer, so wherever several land on one pixel the
+    /// nearest wins, also across calls until the screen is cleared.
+    pub fn draw_points(&mut self, points: &[Vector3<f64>], transform: &Transform, camera: &Camera) -> FrameStats {
+        let view_rotation = camera.view_rotation();
+        let reference_z = (view_rotation * -camera.position).z;
+        let mut stats = FrameStats::default();
+        for &point in points {
+            let view_point = view_rotation * (transform.apply(point) - camera.position);
+            match self.project_view_point(view_point, reference_z, camera.display_surface_z) {
+                Some((x, y)) => {
+                    stats.vertices_projected += 1;
+                    self.set_depth(x, y, view_point.z);
+                }
+                None => stats.vertices_culled += 1,
+            }
+        }
+        stats
+    }
+
+    /// Transforms, projects and draws a wireframe mesh without clearing or
+    /// building the screen. Edges crossing the near plane or leaving the
+    /// screen are clipped rather than dropped.
+    pub fn draw_mesh(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        edges: &[(usize, usize)],
+        transform: &Transform,
+        camera: &Camera,
+    ) -> FrameStats {
+        self.draw_mesh_edges(vertices, edges, transform, camera, |_| true)
+    }
+
+    /// Like `draw_mesh`, blending each edge from the color of its start
+    /// vertex to that of its end. Vertices past the end of `colors` are
+    /// white. Clipped edges keep the colors of the points they are cut at.
+    pub fn draw_mesh_colored(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        edges: &[(usize, usize)],
+        colors: &[Color],
+        transform: &Transform,
+        camera: &Camera,
+    ) -> FrameStats {
+        let color = |vertex: usize| colors.get(vertex).copied().unwrap_or(Color::new(255, 255, 255));
+        let world_points: Vec<_> = vertices.iter().map(|&point| transform.apply(point)).collect();
+        self.draw_world_edges(&world_points, transform.translation, edges, camera, |edge| {
+            let (start, end) = edges[edge];
+            Some(EdgeStyle::Colored(color(start), color(end)))
+        })
+    }
+
+    /// Like `draw_mesh`, drawing only the first `progress * edges.len()` edges
+    /// (rounded down), so sweeping `progress` from 0.0 to 1.0 over several
+    /// frames builds the mesh up one edge at a time.
+    pub fn draw_mesh_progress(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        edges: &[(usize, usize)],
+        transform: &Transform,
+        camera: &Camera,
+        progress: f64,
+    ) -> FrameStats {
+        let count = (progress.clamp(0.0, 1.0) * edges.len() as f64) as usize;
+        self.draw_mesh_edges(vertices, edges, transform, camera, |edge| edge < count)
+    }
+
+    /// Like `draw_mesh`, drawing only the edges whose index `enabled` accepts.
+    pub(crate) fn draw_mesh_edges(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        edges: &[(usize, usize)],
+        transform: &Transform,
+        camera: &Camera,
+        enabled: impl Fn(usize) -> bool,
+    ) -> FrameStats {
+        let world_points: Vec<_> = vertices.iter().map(|&point| transform.apply(point)).collect();
+        self.draw_world_edges(&world_points, transform.translation, edges, camera, |edge| {
+            enabled(edge).then_some(EdgeStyle::Solid)
+        })
+    }
+
+    /// Fills polygonal faces as triangle fans with depth testing, tagging
+    /// face `i` with object id `i + 1` so `face_pixel_counts` reports how
+    /// much of each face is visible, and drawing it with `face_materials[i]`.
+    /// Faces turned away from the camera are handled as `backface_style`
+    /// says. Degenerate faces, which have no normal, and triangles with a
+    /// corner off screen are skipped. `current_object` is left as it was.
+    ///
+    /// Fails without drawing if there are more faces than the 65535 non-zero
+    /// object ids.
+    pub fn fill_faces(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        faces: &[impl AsRef<[usize]>],
+        transform: &Transform,
+        camera: &Camera,
+    ) -> Result<(), ScreenError> {
+        if faces.len() > u16::MAX as usize {
+            return Err(ScreenError::TooManyFaces { count: faces.len() });
+        }
+        let world_points: Vec<_> = vertices.iter().map(|&point| transform.apply(point)).collect();
+        let view_rotation = camera.view_rotation();
+        let reference_z = (view_rotation * -camera.position).z;
+        let corners: Vec<_> = world_points
+            .iter()
+            .map(|&point| {
+                let view_point = view_rotation * (point - camera.position);
+                self.project_view_point(view_point, reference_z, camera.display_surface_z)
+                    .map(|(x, y)| (x, y, view_point.z))
+            })
+            .collect();
+
+        // Faces wind counter-clockwise seen from outside, so their normals
+        // point outwards.
+        let front_facing: Vec<_> = faces
+            .iter()
+            .map(|face| {
+                let points: Vec<_> =
+                    face.as_ref().iter().filter_map(|&index| world_points.get(index).copied()).collect();
+                let (normal, centroid) = (face_normal(&points)?, face_centroid(&points)?);
+                Some(normal.dot(&(camera.position - centroid)) > 0.0)
+            })
+            .collect();
+
+        let (previous_object, previous_glyph) = (self.current_object, self.current_glyph);
+        for (index, face) in faces.iter().enumerate() {
+            match front_facing[index] {
+                None => continue,
+                Some(false) if self.backface_style != BackfaceStyle::Draw => continue,
+                _ => {}
+            }
+            self.current_object = (index + 1) as u16;
+            self.current_glyph = self.face_materials.get(index).copied();
+            for [a, b, c] in fan_triangles(face.as_ref(), &corners) {
+                self.fill_triangle_depth(a, b, c);
+            }
+        }
+        // Ghosts go on top of the front faces, showing through them.
+        if self.backface_style == BackfaceStyle::Ghost {
+            for (index, face) in faces.iter().enumerate().filter(|&(index, _)| front_facing[index] == Some(false)) {
+                self.current_object = (index + 1) as u16;
+                self.current_glyph = Some(self.ghost_char);
+                for [a, b, c] in fan_triangles(face.as_ref(), &corners) {
+                    self.fill_triangle_alpha((a.0, a.1), (b.0, b.1), (c.0, c.1), GHOST_ALPHA);
+                }
+            }
+        }
+        self.current_object = previous_object;
+        self.current_glyph = previous_glyph;
+        Ok(())
+    }
+
+    /// Draws a convex mesh in the style of a technical drawing: edges of
+    /// faces turned towards the camera are solid, and edges shared only by
+    /// faces turned away are dashed. Edges not on any face are solid.
+    /// Degenerate faces, which have no normal, are ignored.
+    pub fn draw_mesh_hidden_dashed(
+        &mut self,
+        vertices: &[Vector3<f64>],
+        edges: &[(usize, usize)],
+        faces: &[impl AsRef<[usize]>],
+        transform: &Transform,
+        camera: &Camera,
+    ) -> FrameStats {
+        let world_points: Vec<_> = vertices.iter().map(|&point| transform.apply(point)).collect();
+        let center = face_centroid(&world_points).unwrap_or(transform.translation);
+        let front_facing: Vec<_> = faces
+            .iter()
+            .map(|face| {
+                let corners: Vec<_> = face
+                    .as_ref()
+                    .iter()
+                    .filter_map(|&index| world_points.get(index).copied())
+                    .collect();
+                let (normal, centroid) = (face_normal(&corners)?, face_centroid(&corners)?);
+                // Winding conventions vary, but on a convex mesh every face
+                // points away from the center.
+                let outward = if normal.dot(&(centroid - center)) < 0.0 { -normal } else { normal };
+                Some(outward.dot(&(camera.position - centroid)) > 0.0)
+            })
+            .collect();
+
+        let style = |index: usize| {
+            let (start, end) = edges[index];
+            let mut adjacent = faces
+                .iter()
+                .zip(&front_facing)
+                .filter(|(face, _)| face_has_edge(face.as_ref(), start, end))
+                .filter_map(|(_, &front)| front)
+                .peekable();
+            let hidden = adjacent.peek().is_some() && !adjacent.any(|front| front);
+            Some(if hidden { EdgeStyle::Dashed } else { EdgeStyle::Solid })
+        };
+        self.draw_world_edges(&world_points, transform.translation, edges, camera, style)
+    }
+
+    /// Draws edges between points already in world space in the style
+    /// `style` picks for each, skipping those it returns `None` for. `origin`
+    /// is the mesh origin the back-vertex test measures normals from.
+    pub(crate) fn draw_world_edges(
+        &mut self,
+        world_points: &[Vector3<f64>],
+        origin: Vector3<f64>,
+        edges: &[(usize, usize)],
+        camera: &Camera,
+        style: impl Fn(usize) -> Option<EdgeStyle>,
+    ) -> FrameStats {
+        let mut stats = FrameStats::default();
+
+        let view_rotation = camera.view_rotation();
+        let view_points: Vec<_> = world_points
+            .iter()
+            .map(|&point| view_rotation * (point - camera.position))
+            .collect();
+        let reference_z = (view_rotation * -camera.position).z;
+        let display_surface_z = camera.display_surface_z;
+
+        if self.is_outside_view(&view_points, reference_z, display_surface_z) {
+            stats.objects_culled = 1;
+            stats.vertices_culled = world_points.len();
+            stats.edges_offscreen = (0..edges.len()).filter(|&index| style(index).is_some()).count();
+            return stats;
+        }
+
+        // Treats the position relative to the mesh origin as the outward
+        // normal, which holds for meshes such as spheres.
+        let facing: Vec<_> = world_points
+            .iter()
+            .map(|&point| {
+                let normal = point - origin;
+                !self.cull_back_vertices || normal.dot(&(camera.position - point)) >= 0.0
+            })
+            .collect();
+
+        let projected_points: Vec<_> = view_points
+            .iter()
+            .zip(&facing)
+            .map(|(&point, &facing)| {
+                facing
+                    .then(|| self.project_view_point(point, reference_z, display_surface_z))
+                    .flatten()
+            })
+            .collect();
+        stats.vertices_projected = projected_points.iter().flatten().count();
+        stats.vertices_culled = view_points.len() - stats.vertices_projected;
+
+        let (nearest, farthest) = view_points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(near, far), point| {
+                (near.min(point.z), far.max(point.z))
+            });
+
+        for (index, &(start, end)) in edges.iter().enumerate() {
+            let Some(style) = style(index) else {
+                continue;
+            };
+            let (Some(&a), Some(&b)) = (view_points.get(start), view_points.get(end)) else {
+                continue;
+            };
+            if !facing[start] || !facing[end] {
+                continue;
+            }
+            let Some((near_a, near_b)) = clip_to_near_plane(a, b, self.near_epsilon) else {
+                stats.edges_behind_camera += 1;
+                continue;
+            };
+            let mut clipped = (near_a, near_b) != (a, b);
+            // Where the kept part starts and ends along the original edge.
+            let s0 = if near_a == a { 0.0 } else { (near_a.z - a.z) / (b.z - a.z) };
+            let s1 = if near_b == b { 1.0 } else { (near_b.z - a.z) / (b.z - a.z) };
+            let depth = (near_a.z + near_b.z) / 2.0;
+            let (Some(a), Some(b)) = (
+                self.view_to_screen(near_a, reference_z, display_surface_z),
+                self.view_to_screen(near_b, reference_z, display_surface_z),
+            ) else {
+                stats.edges_behind_camera += 1;
+                continue;
+            };
+            let Some((p0, p1, t0, t1)) = self.clip_to_screen(a, b) else {
+                stats.edges_offscreen += 1;
+                continue;
+            };
+            clipped |= !self.in_viewport(a) || !self.in_viewport(b);
+            if p0.0.abs_diff(p1.0).max(p0.1.abs_diff(p1.1)) + 1 < self.min_line_length {
+                continue;
+            }
+            // Only ends that are still the mesh's vertices get a gap, not ones
+            // cut by the near plane or the viewport border.
+            let gap = |kept: bool| if kept { self.vertex_gap } else { 0 };
+            let start_kept = t0 == 0.0 && near_a == view_points[start];
+            let end_kept = t1 == 1.0 && near_b == view_points[end];
+            let Some((p0, p1)) = trim_line(p0, p1, gap(start_kept), gap(end_kept)) else {
+                continue;
+            };
+            self.current_glyph = self.depth_char(depth, nearest, farthest);
+            match style {
+                EdgeStyle::Solid => self.draw_line(p0, p1),
+                EdgeStyle::Dashed => self.draw_line_dashed(p0, p1),
+                EdgeStyle::Colored(start_color, end_color) => {
+                    let at = |t: f64| start_color.lerp(end_color, s0 + (s1 - s0) * t);
+                    self.draw_line_colored(p0, p1, at(t0), at(t1))
+                }
+            }
+            stats.edges_drawn += 1;
+            if clipped {
+                stats.edges_clipped += 1;
+            }
+        }
+        self.current_glyph = None;
+
+        if let Some(marker) = self.vertex_char {
+            for &(x, y) in projected_points.iter().flatten() {
+                self.overlay[x + y * self.width] = Some(marker);
+            }
+        }
+        if self.inspect {
+            for (index, &point) in projected_points.iter().enumerate() {
+                if let Some((x, y)) = point {
+                    self.label_vertex(x, y, &index.to_string());
+                }
+            }
+        }
+        stats
+    }
+
+    /// The entry of `depth_chars` for `depth` within `nearest..=farthest`.
+    fn depth_char(&self, depth: f64, nearest: f64, farthest: f64) -> Option<char> {
+        let band = if farthest > nearest {
+            ((depth - nearest) / (farthest - nearest) * self.depth_chars.len() as f64) as usize
+        } else {
+            0
+        };
+        let last = self.depth_chars.len().checked_sub(1)?;
+        Some(self.depth_chars[band.min(last)])
+    }
+
+    /// Places `label` beside the vertex pixel at `(x, y)`: to its right, or to
+    /// its left if it would run off the screen.
+    fn label_vertex(&mut self, x: usize, y: usize, label: &str) {
+        let length = label.chars().count();
+        if x + 1 + length <= self.width || x < length {
+            self.draw_text(x + 1, y, label);
+        } else {
+            self.draw_text(x - length, y, label);
+        }
+    }
+
+    /// Whether a point in continuous screen coordinates lies in the viewport.
+    fn in_viewport(&self, (x, y): (f64, f64)) -> bool {
+        let (left, top, width, height) = self.viewport_rect();
+        (left as f64..(left + width) as f64).contains(&x) && (top as f64..(top + height) as f64).contains(&y)
+    }
+
+    /// Clips a segment in continuous screen coordinates to the viewport
+    /// (Liang-Barsky), returning the pixels at its remaining ends and where
+    /// they lie along it, from 0.0 at `a` to 1.0 at `b`.
+    fn clip_to_screen(&self, a: (f64, f64), b: (f64, f64)) -> Option<ClippedSegment> {
+        let (left, top, width, height) = self.viewport_rect();
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+        let boundaries = [
+            (-dx, a.0 - left as f64),
+            (dx, (left + width) as f64 - a.0),
+            (-dy, a.1 - top as f64),
+            (dy, (top + height) as f64 - a.1),
+        ];
+        for (p, q) in boundaries {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+
+        let pixel = |t: f64| {
+            (
+                ((a.0 + dx * t).round_ties_even() as usize).clamp(left, left + width - 1),
+                ((a.1 + dy * t).round_ties_even() as usize).clamp(top, top + height - 1),
+            )
+        };
+        Some((pixel(t0), pixel(t1), t0, t1))
+    }
+}
+
+/// Fraction of pixels `BackfaceStyle::Ghost` sets on back faces.
+const GHOST_ALPHA: f64 = 0.25;
+
+/// Splits `face` into a fan of triangles around its first corner, leaving
+/// out triangles with a corner that did not project.
+fn fan_triangles<'a>(
+    face: &'a [usize],
+    corners: &'a [Option<(usize, usize, f64)>],
+) -> impl Iterator<Item = [(usize, usize, f64); 3]> + 'a {
+    (1..face.len().saturating_sub(1)).filter_map(move |i| {
+        let [a, b, c] = [face[0], face[i], face[i + 1]].map(|vertex| corners.get(vertex).copied().flatten());
+        Some([a?, b?, c?])
+    })
+}
+
+/// The end pixels of a segment clipped by `Screen::clip_to_screen` and their
+/// positions along the original segment.
+type ClippedSegment = ((usize, usize), (usize, usize), f64, f64);
+
+/// The ends of the line from `start` to `end` after dropping `trim_start`
+/// and `trim_end` pixels from them, or `None` if that leaves none.
+fn trim_line(
+    start: (usize, usize),
+    end: (usize, usize),
+    trim_start: usize,
+    trim_end: usize,
+) -> Option<((usize, usize), (usize, usize))> {
+    if trim_start == 0 && trim_end == 0 {
+        return Some((start, end));
+    }
+    let mut pixels = Vec::new();
+    walk_line(start, end, |x, y| pixels.push((x, y)));
+    (pixels.len() > trim_start + trim_end).then(|| (pixels[trim_start], pixels[pixels.len() - 1 - trim_end]))
+}
+
+/// Whether `start` and `end` are adjacent corners of the polygon `face`.
+fn face_has_edge(face: &[usize], start: usize, end: usize) -> bool {
+    (0..face.len()).any(|i| {
+        let (a, b) = (face[i], face[(i + 1) % face.len()]);
+        (a, b) == (start, end) || (a, b) == (end, start)
+    })
+}
+
+/// Clips a camera-space segment to the part at depth `near_z` or beyond.
+fn clip_to_near_plane(a: Vector3<f64>, b: Vector3<f64>, near_z: f64) -> Option<(Vector3<f64>, Vector3<f64>)> {
+    match (a.z >= near_z, b.z >= near_z) {
+        (true, true) => Some((a, b)),
+        (false, false) => None,
+        (a_visible, _) => {
+            let crossing = a.lerp(&b, (near_z - a.z) / (b.z - a.z));
+            Some(if a_visible { (a, crossing) } else { (crossing, b) })
+        }
+    }
+}
+
+/// Renders one frame per rotation in `rotations`, presenting each one to
+/// `renderer`.
+pub fn run_frames(
+    renderer: &mut dyn Renderer,
+    screen: &mut Screen,
+    vertices: &[Vector3<f64>],
+    edges: &[(usize, usize)],
+    camera: &Camera,
+    rotations: impl IntoIterator<Item = Vector3<f64>>,
+) {
+    for rotation in rotations {
+        render_frame(screen, vertices, edges, rotation, camera);
+        renderer.present(screen);
+    }
+}
+
+/// Renders exactly the recorded `angles`, in order, instead of a live
+/// rotation, so a demo or test sees the same frames every run.
+pub fn replay(
+    renderer: &mut dyn Renderer,
+    screen: &mut Screen,
+    vertices: &[Vector3<f64>],
+    edges: &[(usize, usize)],
+    camera: &Camera,
+    angles: &[Vector3<f64>],
+) {
+    run_frames(renderer, screen, vertices, edges, camera, angles.iter().copied());
+}
+
+/// Reports the time elapsed since a render loop started.
+pub type Clock = Box<dyn FnMut() -> Duration>;
+
+/// Tells a render loop when it has run for `max_duration`; with no maximum
+/// it never expires.
+pub struct Deadline {
+    pub max_duration: Option<Duration>,
+    clock: Clock,
+}
+
+impl Deadline {
+    /// Starts timing now, against the wall clock.
+    pub fn new(max_duration: Option<Duration>) -> Self {
+        let start = Instant::now();
+        Self::with_clock(max_duration, Box::new(move || start.elapsed()))
+    }
+
+    pub fn with_clock(max_duration: Option<Duration>, clock: Clock) -> Self {
+        Self { max_duration, clock }
+    }
+
+    pub fn expired(&mut self) -> bool {
+        self.max_duration.is_some_and(|max_duration| (self.clock)() >= max_duration)
+    }
+}
+
+/// How long before its deadline `precise_sleep` stops sleeping and spins,
+/// covering the typical oversleep of `thread::sleep`.
+pub const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// Blocks for `target`, more accurately than `thread::sleep` alone: it
+/// sleeps for all but `SPIN_THRESHOLD` and busy-waits the rest.
+pub fn precise_sleep(target: Duration) {
+    sleep_spinning(target, SPIN_THRESHOLD);
+}
+
+/// Like `precise_sleep`, busy-waiting for the final `spin` of `target`. A
+/// `spin` of zero sleeps the whole time, saving CPU at the cost of accuracy.
+pub fn sleep_spinning(target: Duration, spin: Duration) {
+    let deadline = Instant::now() + target;
+    if let Some(sleep) = target.checked_sub(spin) {
+        thread::sleep(sleep);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CUBE_EDGES, CUBE_FACES, CUBE_VERTICES};
+
+    fn set_pixels(screen: &Screen) -> usize {
+        screen.pixels.iter().filter(|&&pixel| pixel).count()
+    }
+
+    /// Keeps a copy of every frame presented to it.
+    #[derive(Default)]
+    struct VecRenderer {
+        frames: Vec<String>,
+    }
+
+    impl Renderer for VecRenderer {
+        fn present(&mut self, screen: &Screen) {
+            self.frames.push(screen.buffer().to_string());
+        }
+    }
+
+    /// Records every write it is given separately.
+    #[derive(Default)]
+    struct CountingWriter {
+        writes: Vec<Vec<u8>>,
+        flushes: usize,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frame_stats_count_the_visible_cube() {
+        let mut screen = Screen::new(80, 40);
+        let rotation = Vector3::new(0.3, 0.0, 0.3);
+        let stats = render_frame(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, rotation, &Camera::default());
+        assert_eq!(stats.edges_drawn, 12);
+        assert_eq!(stats.vertices_projected, 8);
+        assert_eq!(stats.vertices_culled, 0);
+        assert_eq!(stats.pixels_set, set_pixels(&screen));
+        assert!(stats.pixels_set > 0);
+    }
+
+    #[test]
+    fn single_pixel_lines_set_one_pixel() {
+        let mut screen = Screen::new(80, 40);
+        screen.draw_line((7, 3), (7, 3));
+        assert_eq!(set_pixels(&screen), 1);
+        assert!(screen.get(7, 3));
+    }
+
+    #[test]
+    fn edges_shorter_than_the_minimum_are_skipped() {
+        let mut screen = Screen::new(80, 40);
+        screen.min_line_length = 100;
+        let rotation = Vector3::new(0.3, 0.0, 0.3);
+        let stats = render_frame(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, rotation, &Camera::default());
+        assert_eq!(stats.edges_drawn, 0);
+        assert_eq!(set_pixels(&screen), 0);
+    }
+
+    #[test]
+    fn back_vertex_culling_keeps_the_near_hemisphere() {
+        let mut screen = Screen::new(80, 40);
+        let (vertices, edges) = crate::uv_sphere(8, 16);
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let all = render_frame(&mut screen, &vertices, &edges, Vector3::zeros(), &camera);
+        assert_eq!(all.vertices_projected, vertices.len());
+
+        screen.cull_back_vertices = true;
+        let front = render_frame(&mut screen, &vertices, &edges, Vector3::zeros(), &camera);
+        // From five radii away a little less than half the sphere is in view.
+        let fraction = front.vertices_projected as f64 / vertices.len() as f64;
+        assert!((0.3..0.5).contains(&fraction), "{}", fraction);
+        assert_eq!(front.vertices_projected + front.vertices_culled, vertices.len());
+        assert!(front.edges_drawn < all.edges_drawn);
+    }
+
+    #[test]
+    fn run_frames_presents_every_frame() {
+        let mut screen = Screen::new(40, 20);
+        let mut renderer = VecRenderer::default();
+        let rotations = (0..5).map(|frame| Vector3::new(0.1, 0.0, 0.1) * frame as f64);
+        run_frames(&mut renderer, &mut screen, &CUBE_VERTICES, &CUBE_EDGES, &Camera::default(), rotations);
+        assert_eq!(renderer.frames.len(), 5);
+        assert_ne!(renderer.frames[0], renderer.frames[1]);
+    }
+
+    #[test]
+    fn supersampling_spreads_a_moving_edge_over_more_pixels() {
+        let mut screen = Screen::new(80, 40);
+        let (rotation, delta) = (Vector3::new(0.3, 0.0, 0.3), Vector3::new(0.2, 0.0, 0.2));
+        let camera = Camera::default();
+        let single = render_frame_supersampled(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, rotation, delta, &camera, 1);
+        assert!(screen.buffer().chars().all(|character| matches!(character, '@' | ' ' | '\n')));
+
+        let blurred = render_frame_supersampled(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, rotation, delta, &camera, 4);
+        assert!(blurred.pixels_set > single.pixels_set);
+        assert_eq!(blurred.edges_drawn, 4 * single.edges_drawn);
+        // Pixels every sub-frame covered are drawn at full brightness, the
+        // rest dimmer.
+        assert!(screen.buffer().contains('@'));
+        assert!(screen.buffer().chars().any(|character| !matches!(character, '@' | ' ' | '\n')));
+    }
+
+    #[test]
+    fn draw_mesh_matches_the_golden_pixel_count() {
+        let mut screen = Screen::new(80, 40);
+        let transform = Transform::from_rotation(Vector3::new(0.3, 0.0, 0.3));
+        screen.draw_mesh(&CUBE_VERTICES, &CUBE_EDGES, &transform, &Camera::default());
+        assert_eq!(set_pixels(&screen), 100);
+    }
+
+    #[test]
+    fn draw_mesh_clips_edges_crossing_the_near_plane() {
+        let mut screen = Screen::new(80, 40);
+        let vertices = [Vector3::new(0.0, 0.0, -10.0), Vector3::new(0.0, 0.0, 5.0)];
+        let stats = screen.draw_mesh(&vertices, &[(0, 1)], &Transform::default(), &Camera::default());
+        assert_eq!((stats.edges_drawn, stats.edges_clipped), (1, 1));
+        assert!(set_pixels(&screen) > 1);
+
+        // With the near plane through the camera, the clipped end cannot be
+        // projected, so the edge is counted as behind the camera.
+        screen.clear();
+        screen.near_epsilon = 0.0;
+        let stats = screen.draw_mesh(&vertices, &[(0, 1)], &Transform::default(), &Camera::default());
+        assert_eq!((stats.edges_drawn, stats.edges_behind_camera), (0, 1));
+        assert_eq!(set_pixels(&screen), 0);
+    }
+
+    #[test]
+    fn persisted_edges_start_at_the_brightest_ramp_character() {
+        let mut screen = Screen::new(40, 20);
+        screen.persistence = 0.5;
+        screen.trailing_newline = false;
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let vertices = [Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0)];
+        render_frame(&mut screen, &vertices, &[(0, 1)], Vector3::zeros(), &camera);
+        let (x, y) = camera.project(&screen, Vector3::zeros()).unwrap();
+        let brightest = *screen.ramp().last().unwrap();
+        assert_eq!(screen.buffer().lines().nth(y).unwrap().chars().nth(x), Some(brightest));
+        assert!(screen.tone_mapping);
+    }
+
+    #[test]
+    fn colored_mesh_edges_blend_towards_the_end_color() {
+        let mut screen = Screen::new(40, 20);
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let (red, blue) = (Color::new(255, 0, 0), Color::new(0, 0, 255));
+        let vertices = [Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0)];
+        let stats = screen.draw_mesh_colored(&vertices, &[(0, 1)], &[red, blue], &Transform::default(), &camera);
+        assert_eq!(stats.edges_drawn, 1);
+        let corners = screen.project_points(&vertices, &camera);
+        let ((start, y), (end, _)) = (corners[0].unwrap(), corners[1].unwrap());
+        assert_eq!(screen.color(start, y), Some(red));
+        assert_eq!(screen.color(end, y), Some(blue));
+        let middle = screen.color((start + end) / 2, y).unwrap();
+        assert!(middle.r > 100 && middle.b > 100 && middle.g == 0, "{:?}", middle);
+
+        // Cut off by the right edge, the edge ends in the color it had there.
+        screen.clear();
+        let long = [vertices[0], Vector3::new(40.0, 0.0, 0.0)];
+        let stats = screen.draw_mesh_colored(&long, &[(0, 1)], &[red, blue], &Transform::default(), &camera);
+        assert_eq!(stats.edges_clipped, 1);
+        let cut = screen.color(39, y).unwrap();
+        assert!(cut.r > cut.b && cut.b > 0, "{:?}", cut);
+    }
+
+    #[test]
+    fn inspect_mode_labels_every_vertex() {
+        let mut screen = Screen::new(80, 40);
+        screen.inspect = true;
+        let transform = Transform::from_rotation(Vector3::new(0.3, 0.0, 0.3));
+        screen.draw_mesh(&CUBE_VERTICES, &CUBE_EDGES, &transform, &Camera::default());
+        let labels: String = (0..40)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .filter_map(|(x, y)| screen.overlay(x, y))
+            .collect();
+        for index in 0..CUBE_VERTICES.len() {
+            assert!(labels.contains(&index.to_string()), "no label for vertex {}", index);
+        }
+    }
+
+    #[test]
+    fn progress_draws_that_share_of_the_edges() {
+        let mut screen = Screen::new(80, 40);
+        let transform = Transform::from_rotation(Vector3::new(0.3, 0.0, 0.3));
+        let mut drawn = |progress| {
+            screen.clear();
+            screen.draw_mesh_progress(&CUBE_VERTICES, &CUBE_EDGES, &transform, &Camera::default(), progress).edges_drawn
+        };
+        assert_eq!(drawn(0.0), 0);
+        assert_eq!(drawn(0.5), 6);
+        assert_eq!(drawn(0.99), 11);
+        assert_eq!(drawn(1.0), 12);
+        assert_eq!(drawn(2.0), 12);
+    }
+
+    #[test]
+    fn deadline_stops_the_loop_after_the_max_duration() {
+        use std::{cell::Cell, rc::Rc};
+
+        let now = Rc::new(Cell::new(Duration::ZERO));
+        let clock = Rc::clone(&now);
+        let mut deadline = Deadline::with_clock(Some(Duration::from_secs(1)), Box::new(move || clock.get()));
+        let mut frames = 0;
+        while !deadline.expired() {
+            frames += 1;
+            now.set(now.get() + Duration::from_millis(100));
+        }
+        assert_eq!(frames, 10);
+
+        let mut forever = Deadline::with_clock(None, Box::new(|| Duration::MAX));
+        assert!(!forever.expired());
+    }
+
+    #[test]
+    fn depth_chars_tell_near_and_far_edges_apart() {
+        let mut screen = Screen::new(80, 40);
+        screen.depth_chars = vec!['N', 'F'];
+        let vertices = [
+            Vector3::new(-1.0, 2.0, 0.0),
+            Vector3::new(1.0, 2.0, 0.0),
+            Vector3::new(-1.0, 0.0, 10.0),
+            Vector3::new(1.0, 0.0, 10.0),
+        ];
+        screen.draw_mesh(&vertices, &[(0, 1), (2, 3)], &Transform::default(), &Camera::default());
+        screen.build();
+        let near_row = screen.buffer().lines().find(|line| line.contains('N')).unwrap();
+        let far_row = screen.buffer().lines().find(|line| line.contains('F')).unwrap();
+        assert!(!near_row.contains('F') && !far_row.contains('N'));
+        assert!(near_row.matches('N').count() > far_row.matches('F').count());
+    }
+
+    #[test]
+    fn project_points_matches_projecting_one_at_a_time() {
+        let screen = Screen::new(80, 40);
+        let mut camera = Camera::new(Vector3::new(0.0, 2.0, -5.0), 1.0);
+        camera.target = Some(Vector3::new(0.5, 0.0, 0.0));
+        let expected: Vec<_> = CUBE_VERTICES.iter().map(|&vertex| camera.project(&screen, vertex)).collect();
+        assert_eq!(screen.project_points(&CUBE_VERTICES, &camera), expected);
+        assert!(expected.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn right_half_viewport_leaves_the_left_half_blank() {
+        let mut screen = Screen::new(80, 40);
+        screen.viewport = Some((40, 0, 40, 40));
+        let transform = Transform::from_rotation(Vector3::new(0.3, 0.0, 0.3));
+        let stats = screen.draw_mesh(&CUBE_VERTICES, &CUBE_EDGES, &transform, &Camera::default());
+        assert_eq!(stats.edges_drawn, 12);
+        let grid = screen.to_grid();
+        assert!(grid.iter().all(|row| row[..40].iter().all(|&pixel| !pixel)));
+        assert!(grid.iter().any(|row| row[40..].iter().any(|&pixel| pixel)));
+    }
+
+    #[test]
+    fn writer_renderer_writes_every_frame_in_order() {
+        let mut renderer = WriterRenderer::new(Vec::new());
+        let mut screen = Screen::new(3, 1);
+        let mut expected = String::new();
+        for x in 0..3 {
+            screen.clear();
+            screen.set(x, 0, true);
+            screen.build();
+            renderer.present(&screen);
+            expected.push_str("\x1b[H");
+            expected.push_str(screen.buffer());
+        }
+        let written = String::from_utf8(renderer.into_inner()).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!(written, "\x1b[H.  \n\x1b[H . \n\x1b[H  .\n");
+    }
+
+    #[test]
+    fn nearer_point_wins_a_shared_pixel() {
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let (near, far) = (Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 5.0));
+        for order in [[(1, near), (2, far)], [(2, far), (1, near)]] {
+            let mut screen = Screen::new(20, 10);
+            screen.enable_object_ids();
+            for (id, point) in order {
+                screen.current_object = id;
+                let stats = screen.draw_points(&[point], &Transform::default(), &camera);
+                assert_eq!(stats.vertices_projected, 1);
+            }
+            assert_eq!(screen.object_id_at(10, 5), Some(1));
+        }
+    }
+
+    #[test]
+    fn hidden_edges_are_dashed_and_visible_ones_solid() {
+        let mut screen = Screen::new(80, 40);
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        // Only the face at z = -1 faces the camera. The degenerate face is
+        // ignored.
+        let mut faces = CUBE_FACES.to_vec();
+        faces.push([0, 0, 0, 0]);
+        let stats = screen.draw_mesh_hidden_dashed(&CUBE_VERTICES, &CUBE_EDGES, &faces, &Transform::default(), &camera);
+        assert_eq!(stats.edges_drawn, 12);
+
+        let edge_pixels = |(start, end): (usize, usize)| {
+            let mut pixels = Vec::new();
+            let project = |index: usize| camera.project(&screen, CUBE_VERTICES[index]).unwrap();
+            walk_line(project(start), project(end), |x, y| pixels.push(screen.get(x, y)));
+            pixels
+        };
+        assert!(edge_pixels((0, 1)).iter().all(|&pixel| pixel));
+        let back = edge_pixels((4, 5));
+        assert!(back.iter().any(|&pixel| pixel) && back.iter().any(|&pixel| !pixel));
+    }
+
+    #[test]
+    fn precise_sleep_waits_at_least_the_target() {
+        for target in [Duration::from_millis(1), Duration::from_millis(5)] {
+            let start = Instant::now();
+            precise_sleep(target);
+            let elapsed = start.elapsed();
+            assert!(elapsed >= target, "{:?}", elapsed);
+            // Generous, as CI machines can be slow to reschedule.
+            assert!(elapsed < target + Duration::from_millis(50), "{:?}", elapsed);
+        }
+        let start = Instant::now();
+        sleep_spinning(Duration::from_millis(3), Duration::ZERO);
+        assert!(start.elapsed() >= Duration::from_millis(3));
+    }
+
+    #[test]
+    fn face_pixel_counts_rank_faces_by_visible_area() {
+        let mut screen = Screen::new(80, 40);
+        screen.enable_object_ids();
+        screen.current_object = 9;
+        screen.backface_style = BackfaceStyle::Cull;
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        // Turned slightly about y, the face at z = -1 fills most of the view
+        // and one side face shows as a sliver.
+        let transform = Transform::from_rotation(Vector3::new(0.0, 0.35, 0.0));
+        screen.fill_faces(&CUBE_VERTICES, &CUBE_FACES, &transform, &camera).unwrap();
+        assert_eq!(screen.current_object, 9);
+
+        let counts = screen.face_pixel_counts();
+        assert_eq!(counts.keys().copied().collect::<Vec<_>>(), [1, 6]);
+        assert!(counts[&1] > 4 * counts[&6], "{:?}", counts);
+    }
+
+    #[test]
+    fn replay_renders_the_recorded_angles_in_order() {
+        let angles = [Vector3::new(0.1, 0.0, 0.2), Vector3::new(0.9, 0.4, 0.0), Vector3::new(0.0, 1.7, 0.3)];
+        let (mut screen, camera) = (Screen::new(40, 20), Camera::default());
+        let mut renderer = VecRenderer::default();
+        replay(&mut renderer, &mut screen, &CUBE_VERTICES, &CUBE_EDGES, &camera, &angles);
+
+        let expected: Vec<_> = angles
+            .iter()
+            .map(|&rotation| {
+                let mut screen = Screen::new(40, 20);
+                render_frame(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, rotation, &camera);
+                screen.take_buffer()
+            })
+            .collect();
+        assert_eq!(renderer.frames, expected);
+    }
+
+    #[test]
+    fn try_project_explains_each_failure() {
+        let (screen, camera) = (Screen::new(80, 40), Camera::default());
+        let (x, y, depth) = screen.try_project(Vector3::new(0.0, 2.0, 0.0), &camera).unwrap();
+        assert_eq!((x, y, depth), (40.0, 20.0, 5.0));
+        assert_eq!(screen.try_project(Vector3::new(0.0, 2.0, -6.0), &camera), Err(ProjectError::BehindCamera));
+        assert_eq!(screen.try_project(Vector3::new(50.0, 2.0, 0.0), &camera), Err(ProjectError::OutOfBounds));
+        assert_eq!(screen.try_project(Vector3::new(f64::NAN, 0.0, 0.0), &camera), Err(ProjectError::NonFinite));
+        assert_eq!(screen.try_project(Vector3::new(0.0, f64::INFINITY, 0.0), &camera), Err(ProjectError::NonFinite));
+    }
+
+    #[test]
+    fn faces_are_filled_with_their_materials() {
+        let mut screen = Screen::new(60, 30);
+        screen.trailing_newline = false;
+        screen.enable_object_ids();
+        screen.backface_style = BackfaceStyle::Cull;
+        screen.face_materials = vec!['A', 'B', 'A', 'B', 'A', 'B'];
+        let transform = Transform::from_rotation(Vector3::new(0.5, 0.6, 0.0));
+        screen.fill_faces(&CUBE_VERTICES, &CUBE_FACES, &transform, &Camera::default()).unwrap();
+        screen.build();
+
+        let mut seen = Vec::new();
+        for (y, line) in screen.buffer().lines().enumerate() {
+            for (x, character) in line.chars().enumerate() {
+                let expected = match screen.object_id_at(x, y).unwrap() {
+                    0 => ' ',
+                    id => screen.face_materials[id as usize - 1],
+                };
+                assert_eq!(character, expected, "({}, {})", x, y);
+                seen.push(character);
+            }
+        }
+        assert!(seen.contains(&'A') && seen.contains(&'B'));
+    }
+
+    #[test]
+    fn stats_separate_behind_clipped_and_offscreen_edges() {
+        let mut screen = Screen::new(80, 40);
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let vertices = [
+            // On screen.
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            // Behind the camera.
+            Vector3::new(0.0, 0.0, -10.0),
+            Vector3::new(1.0, 0.0, -9.0),
+            // Running off the right edge.
+            Vector3::new(50.0, 0.0, 0.0),
+            // Above the top edge.
+            Vector3::new(0.0, 10.0, 0.0),
+            Vector3::new(1.0, 10.0, 0.0),
+        ];
+        let edges = [(0, 1), (2, 3), (0, 4), (5, 6)];
+        let stats = screen.draw_mesh(&vertices, &edges, &Transform::default(), &camera);
+        assert_eq!(stats.edges_drawn, 2);
+        assert_eq!(stats.edges_clipped, 1);
+        assert_eq!(stats.edges_behind_camera, 1);
+        assert_eq!(stats.edges_offscreen, 1);
+    }
+
+    #[test]
+    fn writer_renderer_presents_a_frame_in_one_write() {
+        let mut screen = Screen::new(40, 20);
+        render_frame(&mut screen, &CUBE_VERTICES, &CUBE_EDGES, Vector3::new(0.3, 0.0, 0.3), &Camera::default());
+        let mut renderer = WriterRenderer::new(CountingWriter::default());
+        renderer.present(&screen);
+
+        let out = renderer.into_inner();
+        assert_eq!(out.writes.len(), 1);
+        assert_eq!(out.writes[0], format!("\x1b[H{}", screen.buffer()).into_bytes());
+        assert_eq!(out.flushes, 1);
+    }
+
+    #[test]
+    fn vertex_markers_sit_on_every_projected_vertex() {
+        let mut screen = Screen::new(80, 40);
+        screen.vertex_char = Some('o');
+        let transform = Transform::from_rotation(Vector3::new(0.3, 0.0, 0.3));
+        let camera = Camera::default();
+        screen.draw_mesh(&CUBE_VERTICES, &CUBE_EDGES, &transform, &camera);
+        let corners = screen.project_points(&CUBE_VERTICES.map(|vertex| transform.apply(vertex)), &camera);
+        for (x, y) in corners.into_iter().map(Option::unwrap) {
+            assert_eq!(screen.overlay(x, y), Some('o'));
+        }
+        screen.build();
+        assert_eq!(screen.buffer().matches('o').count(), 8);
+    }
+
+    #[test]
+    fn coinciding_endpoints_set_one_pixel() {
+        let mut screen = Screen::new(80, 40);
+        screen.min_line_length = 1;
+        let vertices = [Vector3::zeros(), Vector3::zeros()];
+        let stats = screen.draw_mesh(&vertices, &[(0, 1)], &Transform::default(), &Camera::default());
+        assert_eq!(stats.edges_drawn, 1);
+        assert_eq!(set_pixels(&screen), 1);
+
+        screen.clear();
+        screen.min_line_length = 2;
+        screen.draw_mesh(&vertices, &[(0, 1)], &Transform::default(), &Camera::default());
+        assert_eq!(set_pixels(&screen), 0);
+    }
+
+    #[test]
+    fn fill_faces_rejects_more_faces_than_object_ids() {
+        let mut screen = Screen::new(8, 8);
+        let faces = vec![[0, 1, 2]; u16::MAX as usize + 1];
+        let result = screen.fill_faces(&CUBE_VERTICES, &faces, &Transform::default(), &Camera::default());
+        assert_eq!(result, Err(ScreenError::TooManyFaces { count: faces.len() }));
+        assert_eq!(set_pixels(&screen), 0);
+    }
+
+    #[test]
+    fn ghosted_back_faces_are_dithered_over_solid_front_faces() {
+        let render = |style| {
+            let mut screen = Screen::new(60, 30);
+            screen.backface_style = style;
+            screen.ghost_char = '`';
+            screen.face_materials = vec!['A', 'B', 'C', 'D', 'E', 'F'];
+            let transform = Transform::from_rotation(Vector3::new(0.5, 0.6, 0.0));
+            screen.fill_faces(&CUBE_VERTICES, &CUBE_FACES, &transform, &Camera::default()).unwrap();
+            screen.build();
+            screen.take_buffer()
+        };
+        let (culled, ghosted) = (render(BackfaceStyle::Cull), render(BackfaceStyle::Ghost));
+        assert_ne!(culled, ghosted);
+
+        let (mut solid, mut ghosts) = (0, 0);
+        for (culled, ghosted) in culled.chars().zip(ghosted.chars()) {
+            match ghosted {
+                '`' => ghosts += 1,
+                _ => assert_eq!(ghosted, culled),
+            }
+            if culled != ' ' && ghosted == culled {
+                solid += 1;
+            }
+        }
+        // Ghosts are sparse, so most of the front faces still show through.
+        assert!(ghosts > 0 && ghosts < solid, "{} ghosts, {} solid", ghosts, solid);
+    }
+
+    #[test]
+    fn vertex_gap_trims_only_ends_at_vertices() {
+        let camera = Camera::new(Vector3::new(0.0, 0.0, -5.0), 1.0);
+        let draw = |end: f64, gap| {
+            let mut screen = Screen::new(40, 20);
+            screen.vertex_gap = gap;
+            let vertices = [Vector3::new(-1.0, 0.0, 0.0), Vector3::new(end, 0.0, 0.0)];
+            screen.draw_mesh(&vertices, &[(0, 1)], &Transform::default(), &camera);
+            let row: Vec<_> = (0..40).filter(|&x| screen.get(x, 10)).collect();
+            (screen.project_points(&vertices, &camera), row)
+        };
+
+        let (corners, solid) = draw(1.0, 0);
+        let (start, end) = (corners[0].unwrap().0, corners[1].unwrap().0);
+        assert_eq!(solid, (start..=end).collect::<Vec<_>>());
+        let (_, gapped) = draw(1.0, 2);
+        assert_eq!(gapped, (start + 2..=end - 2).collect::<Vec<_>>());
+
+        // The far end is cut off by the screen edge, so it keeps its pixels there.
+        let (corners, clipped) = draw(20.0, 2);
+        assert_eq!(corners[1], None);
+        assert_eq!(clipped, (start + 2..40).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,279 @@
+use nalgebra::{Matrix4, Point3, Vector3};
+use std::{collections::HashSet, f64::consts::PI};
+
+use crate::{render::EdgeStyle, Camera, FrameStats, Mesh, Screen, Transform};
+
+/// A wireframe mesh placed in a `Scene`.
+pub struct SceneObject {
+    pub vertices: Vec<Vector3<f64>>,
+    pub edges: Vec<(usize, usize)>,
+    pub transform: Transform,
+    /// Indices into `edges` that are skipped when drawing.
+    pub hidden_edges: HashSet<usize>,
+    /// Radians added to `transform.rotation` about each axis by every
+    /// `Scene::advance`.
+    pub rotation_velocity: Vector3<f64>,
+}
+
+impl SceneObject {
+    pub fn new(vertices: Vec<Vector3<f64>>, edges: Vec<(usize, usize)>) -> Self {
+        Self {
+            vertices,
+            edges,
+            transform: Transform::default(),
+            hidden_edges: HashSet::new(),
+            rotation_velocity: Vector3::zeros(),
+        }
+    }
+
+    /// Steps the rotation on by one frame of `rotation_velocity`, keeping
+    /// each angle within one turn.
+    pub fn advance(&mut self) {
+        self.transform.rotation =
+            (self.transform.rotation + self.rotation_velocity).map(|angle| angle.rem_euclid(2.0 * PI));
+    }
+
+    /// Shows or hides the edge at `index`.
+    pub fn set_edge_enabled(&mut self, index: usize, enabled: bool) {
+        if enabled {
+            self.hidden_edges.remove(&index);
+        } else {
+            self.hidden_edges.insert(index);
+        }
+    }
+
+    pub fn is_edge_enabled(&self, index: usize) -> bool {
+        !self.hidden_edges.contains(&index)
+    }
+}
+
+#[derive(Default)]
+pub struct Scene {
+    objects: Vec<SceneObject>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty scene with room for `capacity` objects.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            objects: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, object: SceneObject) {
+        self.objects.push(object);
+    }
+
+    pub fn objects(&self) -> &[SceneObject] {
+        &self.objects
+    }
+
+    pub fn objects_mut(&mut self) -> &mut [SceneObject] {
+        &mut self.objects
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.objects.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.objects.reserve(additional);
+    }
+
+    /// Advances every object's rotation by one frame, each at its own
+    /// `rotation_velocity`.
+    pub fn advance(&mut self) {
+        self.objects.iter_mut().for_each(SceneObject::advance);
+    }
+
+    /// Draws every object, tagging each with its position in the scene plus
+    /// one as the object id. Ids stop at `u16::MAX`, which all objects from
+    /// there on share. Returns the combined counts; `current_object` is left
+    /// as it was.
+    pub fn draw(&self, screen: &mut Screen, camera: &Camera) -> FrameStats {
+        let mut stats = FrameStats::default();
+        let previous_object = screen.current_object;
+        for (index, object) in self.objects.iter().enumerate() {
+            screen.current_object = u16::try_from(index + 1).unwrap_or(u16::MAX);
+            stats += screen.draw_mesh_edges(
+                &object.vertices,
+                &object.edges,
+                &object.transform,
+                camera,
+                |edge| object.is_edge_enabled(edge),
+            );
+        }
+        screen.current_object = previous_object;
+        stats
+    }
+}
+
+/// A node in a scene graph. Its transform is relative to its parent, so
+/// moving a node carries all of its descendants along.
+#[derive(Default)]
+pub struct Node {
+    pub transform: Transform,
+    /// Vertices and edges drawn at this node, if any.
+    pub mesh: Option<Mesh>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            transform,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_mesh(transform: Transform, vertices: Vec<Vector3<f64>>, edges: Vec<(usize, usize)>) -> Self {
+        Self {
+            transform,
+            mesh: Some((vertices, edges)),
+            children: Vec::new(),
+        }
+    }
+
+    /// Calls `visit` for this node and every descendant, depth first, with
+    /// the node's world transform: `parent` composed with the local
+    /// transforms on the way down.
+    pub fn traverse(&self, parent: &Matrix4<f64>, visit: &mut impl FnMut(&Node, &Matrix4<f64>)) {
+        let world = parent * self.transform.to_matrix();
+        visit(self, &world);
+        for child in &self.children {
+            child.traverse(&world, visit);
+        }
+    }
+
+    /// Draws the meshes of this subtree with their world transforms and
+    /// returns the combined counts.
+    pub fn draw(&self, screen: &mut Screen, camera: &Camera) -> FrameStats {
+        let mut stats = FrameStats::default();
+        self.traverse(&Matrix4::identity(), &mut |node, world| {
+            if let Some((vertices, edges)) = &node.mesh {
+                let world_points: Vec<_> = vertices
+                    .iter()
+                    .map(|point| world.transform_point(&Point3::from(*point)).coords)
+                    .collect();
+                let origin = world.transform_point(&Point3::origin()).coords;
+                stats += screen.draw_world_edges(&world_points, origin, edges, camera, |_| Some(EdgeStyle::Solid));
+            }
+        });
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CUBE_EDGES, CUBE_VERTICES};
+
+    fn cube() -> SceneObject {
+        let mut object = SceneObject::new(CUBE_VERTICES.to_vec(), CUBE_EDGES.to_vec());
+        object.transform.rotation = Vector3::new(0.3, 0.0, 0.3);
+        object
+    }
+
+    #[test]
+    fn with_capacity_preallocates_an_empty_scene() {
+        let mut scene = Scene::with_capacity(100);
+        assert!(scene.capacity() >= 100);
+        assert_eq!(scene.len(), 0);
+        assert!(scene.is_empty());
+
+        scene.reserve(200);
+        assert!(scene.capacity() >= 200);
+    }
+
+    #[test]
+    fn hidden_edges_are_not_drawn() {
+        let mut scene = Scene::new();
+        scene.push(cube());
+        let mut screen = Screen::new(80, 40);
+        assert_eq!(scene.draw(&mut screen, &Camera::default()).edges_drawn, 12);
+
+        scene.objects_mut()[0].set_edge_enabled(3, false);
+        assert!(!scene.objects()[0].is_edge_enabled(3));
+        assert_eq!(scene.draw(&mut screen, &Camera::default()).edges_drawn, 11);
+
+        scene.objects_mut()[0].set_edge_enabled(3, true);
+        assert_eq!(scene.draw(&mut screen, &Camera::default()).edges_drawn, 12);
+    }
+
+    #[test]
+    fn object_ids_stop_at_the_largest_id_and_current_object_is_kept() {
+        let mut scene = Scene::with_capacity(u16::MAX as usize + 1);
+        for _ in 0..u16::MAX {
+            scene.push(SceneObject::new(Vec::new(), Vec::new()));
+        }
+        scene.push(cube());
+        let mut screen = Screen::new(80, 40);
+        screen.enable_object_ids();
+        screen.current_object = 3;
+        assert_eq!(scene.draw(&mut screen, &Camera::default()).edges_drawn, 12);
+        assert_eq!(screen.current_object, 3);
+
+        let ids: HashSet<_> = (0..40)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .filter_map(|(x, y)| screen.object_id_at(x, y))
+            .filter(|&id| id != 0)
+            .collect();
+        assert_eq!(ids, HashSet::from([u16::MAX]));
+    }
+
+    #[test]
+    fn child_follows_its_rotated_parent() {
+        let mut parent = Node::new(Transform {
+            rotation: Vector3::new(0.0, 0.0, PI / 2.0),
+            translation: Vector3::new(1.0, 0.0, 0.0),
+            ..Transform::default()
+        });
+        parent.children.push(Node::new(Transform {
+            translation: Vector3::new(2.0, 0.0, 0.0),
+            ..Transform::default()
+        }));
+
+        let mut origins = Vec::new();
+        parent.traverse(&Matrix4::identity(), &mut |_, world| {
+            origins.push(world.transform_point(&Point3::origin()).coords);
+        });
+        assert_eq!(origins.len(), 2);
+        assert!((origins[0] - Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-12);
+        // The parent's quarter turn about z swings the child's offset onto +y.
+        assert!((origins[1] - Vector3::new(1.0, 2.0, 0.0)).norm() < 1e-12, "{:?}", origins[1]);
+    }
+
+    #[test]
+    fn advance_spins_each_object_at_its_own_rate() {
+        let mut scene = Scene::new();
+        let (mut slow, mut fast) = (cube(), cube());
+        slow.rotation_velocity = Vector3::new(0.0, 0.01, 0.0);
+        fast.rotation_velocity = Vector3::new(0.0, 0.05, 0.0);
+        scene.push(slow);
+        scene.push(fast);
+        for _ in 0..10 {
+            scene.advance();
+        }
+        let angles: Vec<_> = scene.objects().iter().map(|object| object.transform.rotation).collect();
+        assert!((angles[0] - Vector3::new(0.3, 0.1, 0.3)).norm() < 1e-12, "{:?}", angles[0]);
+        assert!((angles[1] - Vector3::new(0.3, 0.5, 0.3)).norm() < 1e-12, "{:?}", angles[1]);
+
+        // Angles wrap around rather than growing without bound.
+        for _ in 0..200 {
+            scene.advance();
+        }
+        assert!(scene.objects().iter().all(|object| object.transform.rotation.y < 2.0 * PI));
+    }
+}
@@ -0,0 +1,225 @@
+use std::io::{self, stdout, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Erases the display and homes the cursor, for terminals where
+/// `clearscreen` cannot work out how to clear.
+pub const FALLBACK_CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// Clears the terminal with `clearscreen`, falling back to `FALLBACK_CLEAR`
+/// when it reports an error so the program keeps running.
+pub fn clear_terminal() -> io::Result<()> {
+    clear_with_fallback(&mut stdout().lock(), clearscreen::clear)
+}
+
+/// Runs `clear`, writing `FALLBACK_CLEAR` to `out` if it fails.
+pub fn clear_with_fallback<E>(out: &mut impl Write, clear: impl FnOnce() -> Result<(), E>) -> io::Result<()> {
+    if clear().is_err() {
+        out.write_all(FALLBACK_CLEAR.as_bytes())?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// How the terminal is prepared before the first frame. Every frame homes
+/// the cursor and overwrites the last one, so this only decides what happens
+/// to the text already on screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum ClearStrategy {
+    /// Clears with `clearscreen`, as `clear_terminal` does.
+    #[default]
+    FullClear,
+    /// Only homes the cursor, leaving text outside the frame in place.
+    CursorHome,
+    /// Draws on the alternate screen, restoring the original on `end`.
+    AltScreen,
+    /// Leaves the terminal alone.
+    None,
+}
+
+impl ClearStrategy {
+    /// Prepares stdout, clearing with `clearscreen` for `FullClear`.
+    pub fn begin(self) -> io::Result<()> {
+        self.begin_with(&mut stdout().lock(), clearscreen::clear)
+    }
+
+    /// Prepares `out`, calling `clear` for `FullClear` and falling back to
+    /// `FALLBACK_CLEAR` if it fails.
+    pub fn begin_with<E>(self, out: &mut impl Write, clear: impl FnOnce() -> Result<(), E>) -> io::Result<()> {
+        let escape = match self {
+            ClearStrategy::FullClear => return clear_with_fallback(out, clear),
+            ClearStrategy::CursorHome => "\x1b[H",
+            ClearStrategy::AltScreen => "\x1b[?1049h\x1b[H",
+            ClearStrategy::None => return Ok(()),
+        };
+        out.write_all(escape.as_bytes())?;
+        out.flush()
+    }
+
+    /// Undoes `begin` on stdout.
+    pub fn end(self) -> io::Result<()> {
+        self.end_with(&mut stdout().lock())
+    }
+
+    /// Undoes `begin_with` on `out`; only `AltScreen` has anything to undo.
+    pub fn end_with(self, out: &mut impl Write) -> io::Result<()> {
+        if self == ClearStrategy::AltScreen {
+            out.write_all(b"\x1b[?1049l")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Resets colors and moves to a fresh line below the last frame, so the
+/// shell prompt is not drawn over it on exit.
+pub fn restore_terminal() -> io::Result<()> {
+    let mut out = stdout().lock();
+    out.write_all(b"\x1b[0m\n")?;
+    out.flush()
+}
+
+/// Cell height over width assumed when the terminal cannot be asked.
+pub const DEFAULT_CHAR_ASPECT: f64 = 2.0;
+
+/// Asks the terminal for its cell size in pixels (`CSI 16 t`) and returns the
+/// cell height over width, or `DEFAULT_CHAR_ASPECT` if stdin and stdout are
+/// not a terminal or it does not answer. Waits at most 200ms for a reply.
+///
+/// Uses `stty` to read the reply unbuffered, so this only queries on Unix.
+pub fn query_char_aspect() -> f64 {
+    query_cell_size_report()
+        .as_deref()
+        .and_then(parse_cell_size_report)
+        .unwrap_or(DEFAULT_CHAR_ASPECT)
+}
+
+fn query_cell_size_report() -> Option<String> {
+    if !stdout().is_terminal() {
+        return None;
+    }
+    let mut input = RawInput::enable(2)?;
+    let mut response = Vec::new();
+    let mut out = stdout().lock();
+    if out.write_all(b"\x1b[16t").and_then(|()| out.flush()).is_ok() {
+        while let Some(byte) = input.read_byte() {
+            response.push(byte);
+            if byte == b't' {
+                break;
+            }
+        }
+    }
+    String::from_utf8(response).ok()
+}
+
+/// Puts the terminal into unbuffered, non-echoing input for as long as it
+/// is alive, so key presses can be read one at a time. The previous mode is
+/// restored on drop.
+///
+/// Ctrl-C no longer raises SIGINT, which would exit without that restore;
+/// it is read as the byte 0x03 instead, which `Controls` treats as quit.
+///
+/// Uses `stty`, so this is only available on Unix.
+pub struct RawInput {
+    saved: String,
+}
+
+impl RawInput {
+    /// Switches stdin to raw input, with reads waiting up to `timeout`
+    /// tenths of a second for a byte; 0 makes them return immediately.
+    /// `None` if stdin is not a terminal or `stty` fails.
+    pub fn enable(timeout: u8) -> Option<Self> {
+        if !io::stdin().is_terminal() {
+            return None;
+        }
+        let saved = stty(&["-g"])?;
+        let timeout = timeout.to_string();
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", &timeout])?;
+        Some(Self {
+            saved: saved.trim().to_string(),
+        })
+    }
+
+    /// The next byte of input, or `None` if none arrived within the timeout.
+    pub fn read_byte(&mut self) -> Option<u8> {
+        let mut byte = [0];
+        match io::stdin().read(&mut byte) {
+            Ok(1) => Some(byte[0]),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for RawInput {
+    fn drop(&mut self) {
+        stty(&[&self.saved]);
+    }
+}
+
+/// Runs `stty` on the terminal, returning its output if it succeeded.
+fn stty(args: &[&str]) -> Option<String> {
+    Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+}
+
+/// Parses a cell size report of the form `ESC [ 6 ; height ; width t` into
+/// the cell height over width.
+pub fn parse_cell_size_report(response: &str) -> Option<f64> {
+    let fields = response.strip_prefix("\x1b[6;")?.strip_suffix('t')?;
+    let (height, width) = fields.split_once(';')?;
+    let (height, width): (f64, f64) = (height.parse().ok()?, width.parse().ok()?);
+    (height > 0.0 && width > 0.0).then(|| height / width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_clear_falls_back_to_the_escape_sequence() {
+        let mut out = Vec::new();
+        clear_with_fallback(&mut out, || Err("unsupported terminal")).unwrap();
+        assert_eq!(out, FALLBACK_CLEAR.as_bytes());
+
+        let mut out = Vec::new();
+        clear_with_fallback(&mut out, || Ok::<(), ()>(())).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn parses_cell_size_reports() {
+        assert_eq!(parse_cell_size_report("\x1b[6;20;10t"), Some(2.0));
+        assert_eq!(parse_cell_size_report("\x1b[6;17;8t"), Some(17.0 / 8.0));
+        assert_eq!(parse_cell_size_report("\x1b[6;20t"), None);
+        assert_eq!(parse_cell_size_report("\x1b[6;20;0t"), None);
+        assert_eq!(parse_cell_size_report("\x1b[4;600;800t"), None);
+    }
+
+    #[test]
+    fn clear_strategies_write_their_escapes() {
+        let written = |strategy: ClearStrategy| {
+            let (mut begin, mut end) = (Vec::new(), Vec::new());
+            strategy.begin_with(&mut begin, || Err("no terminal")).unwrap();
+            strategy.end_with(&mut end).unwrap();
+            (String::from_utf8(begin).unwrap(), String::from_utf8(end).unwrap())
+        };
+        assert_eq!(written(ClearStrategy::FullClear), (FALLBACK_CLEAR.to_string(), String::new()));
+        assert_eq!(written(ClearStrategy::CursorHome), ("\x1b[H".to_string(), String::new()));
+        assert_eq!(written(ClearStrategy::AltScreen), ("\x1b[?1049h\x1b[H".to_string(), "\x1b[?1049l".to_string()));
+        assert_eq!(written(ClearStrategy::None), (String::new(), String::new()));
+
+        let mut cleared = false;
+        let mut out = Vec::new();
+        ClearStrategy::FullClear.begin_with(&mut out, || {
+            cleared = true;
+            Ok::<(), ()>(())
+        })
+        .unwrap();
+        assert!(cleared && out.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use nalgebra::{Matrix4, Vector3};
+
+use crate::{rotate_point_ordered, rotation_matrix, RotationOrder};
+
+/// Places a mesh in the world: scaled and rotated about its origin, then
+/// translated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    /// Per-axis scale factors, applied before the rotation.
+    pub scale: Vector3<f64>,
+    pub rotation: Vector3<f64>,
+    pub rotation_order: RotationOrder,
+    pub translation: Vector3<f64>,
+}
+
+impl Transform {
+    pub fn from_rotation(rotation: Vector3<f64>) -> Self {
+        Self {
+            rotation,
+            ..Self::default()
+        }
+    }
+
+    pub fn apply(&self, point: Vector3<f64>) -> Vector3<f64> {
+        let scaled = point.component_mul(&self.scale);
+        rotate_point_ordered(scaled, self.rotation, self.rotation_order) + self.translation
+    }
+
+    /// The homogeneous matrix equivalent to `apply`, for composing transforms.
+    pub fn to_matrix(&self) -> Matrix4<f64> {
+        Matrix4::new_translation(&self.translation)
+            * rotation_matrix(self.rotation, self.rotation_order).to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            scale: Vector3::repeat(1.0),
+            rotation: Vector3::zeros(),
+            rotation_order: RotationOrder::default(),
+            translation: Vector3::zeros(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use super::*;
+    use crate::CUBE_VERTICES;
+
+    #[test]
+    fn non_uniform_scale_stretches_one_axis() {
+        let transform = Transform {
+            scale: Vector3::new(2.0, 1.0, 1.0),
+            ..Transform::default()
+        };
+        let extent = |points: &[Vector3<f64>]| {
+            let max = points.iter().fold(Vector3::repeat(f64::NEG_INFINITY), |max, point| max.sup(point));
+            let min = points.iter().fold(Vector3::repeat(f64::INFINITY), |min, point| min.inf(point));
+            max - min
+        };
+        let scaled = CUBE_VERTICES.map(|vertex| transform.apply(vertex));
+        assert_eq!(extent(&scaled), Vector3::new(4.0, 2.0, 2.0));
+        assert_eq!(extent(&scaled).x, 2.0 * extent(&CUBE_VERTICES).x);
+    }
+
+    #[test]
+    fn matrix_matches_apply() {
+        let transform = Transform {
+            scale: Vector3::new(2.0, 0.5, 1.5),
+            rotation: Vector3::new(0.3, -1.2, 0.8),
+            rotation_order: RotationOrder::Zyx,
+            translation: Vector3::new(1.0, 2.0, 3.0),
+        };
+        for vertex in CUBE_VERTICES {
+            let through_matrix = transform.to_matrix().transform_point(&Point3::from(vertex)).coords;
+            assert!((through_matrix - transform.apply(vertex)).norm() < 1e-12);
+        }
+    }
+}
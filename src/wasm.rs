@@ -0,0 +1,34 @@
+use nalgebra::Vector3;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{render_frame, Camera, Screen, CUBE_EDGES, CUBE_VERTICES};
+
+/// Renders one frame of the spinning cube at `angle` and returns it as text,
+/// ready to be placed inside a `<pre>` element.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn render_frame_to_string(angle: f64, width: u32, height: u32) -> String {
+    let mut screen = Screen::new(width as usize, height as usize);
+    screen.trailing_newline = false;
+
+    render_frame(
+        &mut screen,
+        &CUBE_VERTICES,
+        &CUBE_EDGES,
+        Vector3::new(angle, 0.0, angle),
+        &Camera::default(),
+    );
+    screen.buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_non_empty_frame() {
+        let frame = render_frame_to_string(0.5, 40, 20);
+        assert_eq!(frame.lines().count(), 20);
+        assert!(frame.chars().any(|character| character != ' ' && character != '\n'));
+    }
+}